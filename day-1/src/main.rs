@@ -1,14 +1,79 @@
+mod profile;
+mod readings;
+
+use profile::DepthProfile;
+use readings::RepairPolicy;
+
 fn main() {
     let contents = include_str!("input.txt");
-    let values = contents
-        .split("\n")
-        .filter_map(|line| line.parse::<u32>().ok())
-        .collect::<Vec<u32>>();
+
+    // `--repair skip|interpolate|fail` picks what happens to unparseable lines
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut policy = RepairPolicy::Skip;
+    if let Some(i) = args.iter().position(|arg| arg == "--repair") {
+        let name = args.get(i + 1).expect("--repair needs a policy");
+        policy = RepairPolicy::parse(name).expect("unknown repair policy");
+        args.drain(i..=i + 1);
+    }
+
+    // `cargo run -- validate` lists bad lines without solving
+    if args.first().map(|s| s.as_str()) == Some("validate") {
+        let bad = readings::scan(contents)
+            .into_iter()
+            .filter_map(|r| r.err())
+            .collect::<Vec<_>>();
+        for line in &bad {
+            println!("{}", line);
+        }
+        println!("{} bad lines", bad.len());
+        return;
+    }
+
+    let readings = match readings::parse(contents, policy) {
+        Ok(readings) => readings,
+        Err(bad) => {
+            for line in &bad {
+                eprintln!("{}", line);
+            }
+            eprintln!("repair: fail ({} bad lines)", bad.len());
+            std::process::exit(1);
+        }
+    };
+    let values = readings.values;
+    if values.is_empty() {
+        eprintln!(
+            "repair: {} ({} bad lines, no readings left)",
+            readings.policy,
+            readings.bad.len()
+        );
+        std::process::exit(1);
+    }
+    let repair = format!(
+        "repair: {} ({} bad lines)",
+        readings.policy,
+        readings.bad.len()
+    );
+
+    // `cargo run -- report [text|json]` prints a depth profile instead
+    if args.first().map(|s| s.as_str()) == Some("report") {
+        let profile = DepthProfile::build(&values).unwrap();
+        match args.get(1).map(|s| s.as_str()) {
+            None | Some("text") => print!("{}\n{}", repair, profile.to_text()),
+            Some("json") => {
+                // keep stdout as plain json
+                eprintln!("{}", repair);
+                println!("{}", profile.to_json());
+            }
+            Some(format) => panic!("unknown report format {}", format),
+        }
+        return;
+    }
+
+    println!("{}", repair);
     {
         let mut prev = values[0];
         let mut increasing_count = 0;
-        for i in 1..(values.len()) {
-            let item = values[i];
+        for &item in &values[1..] {
             if item > prev {
                 increasing_count += 1;
             }
@@ -18,7 +83,8 @@ fn main() {
     }
 
     {
-        let mut prev = values[0] + values[1] + values[2];
+        // fewer than three readings have no windows to compare
+        let mut prev = values.iter().take(3).sum::<u32>();
         let mut increasing_count = 0;
 
         for i in 3..(values.len()) {
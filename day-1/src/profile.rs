@@ -0,0 +1,225 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Run {
+    pub start: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drop {
+    pub at: usize,
+    pub amount: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plateau {
+    pub start: usize,
+    pub len: usize,
+    pub value: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extreme {
+    pub at: usize,
+    pub value: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthProfile {
+    pub count: usize,
+    pub longest_increase: Run,
+    pub largest_drop: Option<Drop>,
+    pub plateaus: Vec<Plateau>,
+    pub min: Extreme,
+    pub max: Extreme,
+    pub mean: f64,
+}
+
+impl DepthProfile {
+    // positions are indexes into `values`; ties go to the earliest position
+    pub fn build(values: &[u32]) -> Option<DepthProfile> {
+        let first = *values.first()?;
+        let mut longest_increase = Run { start: 0, len: 1 };
+        let mut increase = longest_increase;
+        let mut largest_drop: Option<Drop> = None;
+        let mut plateaus = Vec::new();
+        let mut plateau = Plateau {
+            start: 0,
+            len: 1,
+            value: first,
+        };
+        let mut min = Extreme {
+            at: 0,
+            value: first,
+        };
+        let mut max = min;
+        let mut sum = first as u64;
+
+        for (i, pair) in values.windows(2).enumerate() {
+            let (prev, item, at) = (pair[0], pair[1], i + 1);
+            sum += item as u64;
+
+            if item > prev {
+                increase.len += 1;
+            } else {
+                increase = Run { start: at, len: 1 };
+            }
+            if increase.len > longest_increase.len {
+                longest_increase = increase;
+            }
+
+            if item < prev {
+                let amount = prev - item;
+                if largest_drop.is_none_or(|d| amount > d.amount) {
+                    largest_drop = Some(Drop { at, amount });
+                }
+            }
+
+            if item == prev {
+                plateau.len += 1;
+            } else {
+                if plateau.len > 1 {
+                    plateaus.push(plateau);
+                }
+                plateau = Plateau {
+                    start: at,
+                    len: 1,
+                    value: item,
+                };
+            }
+
+            if item < min.value {
+                min = Extreme { at, value: item };
+            }
+            if item > max.value {
+                max = Extreme { at, value: item };
+            }
+        }
+        if plateau.len > 1 {
+            plateaus.push(plateau);
+        }
+
+        Some(DepthProfile {
+            count: values.len(),
+            longest_increase,
+            largest_drop,
+            plateaus,
+            min,
+            max,
+            mean: sum as f64 / values.len() as f64,
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("readings: {}\n", self.count));
+        out.push_str(&format!(
+            "longest increase: {} readings from {} to {}\n",
+            self.longest_increase.len,
+            self.longest_increase.start,
+            self.longest_increase.start + self.longest_increase.len - 1
+        ));
+        match self.largest_drop {
+            Some(drop) => out.push_str(&format!("largest drop: {} at {}\n", drop.amount, drop.at)),
+            None => out.push_str("largest drop: none\n"),
+        }
+        out.push_str(&format!("plateaus: {}\n", self.plateaus.len()));
+        for plateau in &self.plateaus {
+            out.push_str(&format!(
+                "  {} x{} from {}\n",
+                plateau.value, plateau.len, plateau.start
+            ));
+        }
+        out.push_str(&format!("min: {} at {}\n", self.min.value, self.min.at));
+        out.push_str(&format!("max: {} at {}\n", self.max.value, self.max.at));
+        out.push_str(&format!("mean: {:.3}\n", self.mean));
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let drop = match self.largest_drop {
+            Some(drop) => format!("{{\"at\":{},\"amount\":{}}}", drop.at, drop.amount),
+            None => "null".to_string(),
+        };
+        let plateaus = self
+            .plateaus
+            .iter()
+            .map(|p| {
+                format!(
+                    "{{\"start\":{},\"len\":{},\"value\":{}}}",
+                    p.start, p.len, p.value
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"count\":{},\"longest_increase\":{{\"start\":{},\"len\":{}}},\"largest_drop\":{},\"plateaus\":[{}],\"min\":{{\"at\":{},\"value\":{}}},\"max\":{{\"at\":{},\"value\":{}}},\"mean\":{}}}",
+            self.count,
+            self.longest_increase.start,
+            self.longest_increase.len,
+            drop,
+            plateaus,
+            self.min.at,
+            self.min.value,
+            self.max.at,
+            self.max.value,
+            self.mean
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn runs_and_plateaus_at_the_end() {
+        let profile = DepthProfile::build(&[5, 3, 4, 6, 8, 8, 8]).unwrap();
+        assert_eq!(profile.count, 7);
+        assert_eq!(profile.longest_increase, Run { start: 1, len: 4 });
+        assert_eq!(profile.largest_drop, Some(Drop { at: 1, amount: 2 }));
+        assert_eq!(
+            profile.plateaus,
+            vec![Plateau {
+                start: 4,
+                len: 3,
+                value: 8
+            }]
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_earliest() {
+        let profile = DepthProfile::build(&[2, 9, 1, 9, 1, 4, 1]).unwrap();
+        assert_eq!(profile.min, Extreme { at: 2, value: 1 });
+        assert_eq!(profile.max, Extreme { at: 1, value: 9 });
+        assert_eq!(profile.largest_drop, Some(Drop { at: 2, amount: 8 }));
+        assert_eq!(profile.longest_increase, Run { start: 0, len: 2 });
+    }
+
+    #[test]
+    fn single_reading() {
+        assert_eq!(DepthProfile::build(&[]), None);
+        let profile = DepthProfile::build(&[7]).unwrap();
+        assert_eq!(profile.longest_increase, Run { start: 0, len: 1 });
+        assert_eq!(profile.largest_drop, None);
+        assert!(profile.plateaus.is_empty());
+        assert_eq!(profile.min, profile.max);
+        assert_eq!(profile.mean, 7.0);
+        assert!(profile.to_text().contains("largest drop: none\n"));
+    }
+
+    #[test]
+    fn json_shape() {
+        let profile = DepthProfile::build(&[3, 3, 1]).unwrap();
+        assert_eq!(
+            profile.to_json(),
+            "{\"count\":3,\"longest_increase\":{\"start\":0,\"len\":1},\
+             \"largest_drop\":{\"at\":2,\"amount\":2},\
+             \"plateaus\":[{\"start\":0,\"len\":2,\"value\":3}],\
+             \"min\":{\"at\":2,\"value\":1},\"max\":{\"at\":0,\"value\":3},\
+             \"mean\":2.3333333333333335}"
+        );
+        let single = DepthProfile::build(&[7]).unwrap().to_json();
+        assert!(single.contains("\"largest_drop\":null,\"plateaus\":[],"));
+    }
+}
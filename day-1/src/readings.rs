@@ -0,0 +1,162 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepairPolicy {
+    Skip,
+    Interpolate,
+    Fail,
+}
+
+impl RepairPolicy {
+    pub fn parse(s: &str) -> Option<RepairPolicy> {
+        match s {
+            "skip" => Some(RepairPolicy::Skip),
+            "interpolate" => Some(RepairPolicy::Interpolate),
+            "fail" => Some(RepairPolicy::Fail),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RepairPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            RepairPolicy::Skip => "skip",
+            RepairPolicy::Interpolate => "interpolate",
+            RepairPolicy::Fail => "fail",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadLine {
+    pub line: usize,
+    pub text: String,
+}
+
+impl fmt::Display for BadLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {:?}", self.line, self.text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Readings {
+    pub values: Vec<u32>,
+    pub bad: Vec<BadLine>,
+    pub policy: RepairPolicy,
+}
+
+// a single trailing newline is not a bad reading; any other line that doesn't
+// parse is, and is reported by its 1-based line number
+pub fn scan(contents: &str) -> Vec<Result<u32, BadLine>> {
+    let contents = contents.strip_suffix('\n').unwrap_or(contents);
+    contents
+        .split("\n")
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse::<u32>().map_err(|_| BadLine {
+                line: i + 1,
+                text: line.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub fn parse(contents: &str, policy: RepairPolicy) -> Result<Readings, Vec<BadLine>> {
+    let scanned = scan(contents);
+    let bad = scanned
+        .iter()
+        .filter_map(|r| r.as_ref().err().cloned())
+        .collect::<Vec<_>>();
+
+    let values = match policy {
+        RepairPolicy::Fail if !bad.is_empty() => return Err(bad),
+        RepairPolicy::Skip | RepairPolicy::Fail => scanned
+            .iter()
+            .filter_map(|r| r.as_ref().ok().cloned())
+            .collect(),
+        RepairPolicy::Interpolate => interpolate(&scanned),
+    };
+
+    Ok(Readings {
+        values,
+        bad,
+        policy,
+    })
+}
+
+// fill each gap linearly between the nearest good readings on either side;
+// gaps at either end take the value of their only neighbour
+fn interpolate(scanned: &[Result<u32, BadLine>]) -> Vec<u32> {
+    let known = scanned
+        .iter()
+        .enumerate()
+        .filter_map(|(i, r)| r.as_ref().ok().map(|v| (i, *v)))
+        .collect::<Vec<_>>();
+    if known.is_empty() {
+        return Vec::new();
+    }
+
+    let mut out = Vec::with_capacity(scanned.len());
+    let mut next = 0;
+    for i in 0..scanned.len() {
+        while next < known.len() && known[next].0 < i {
+            next += 1;
+        }
+        let value = match (next.checked_sub(1).map(|p| known[p]), known.get(next)) {
+            (_, Some(&(at, value))) if at == i => value,
+            (Some((l_at, l)), Some(&(r_at, r))) => {
+                let span = (r_at - l_at) as i64;
+                let offset = (i - l_at) as i64;
+                (l as i64 + (r as i64 - l as i64) * offset / span) as u32
+            }
+            (Some((_, l)), None) => l,
+            (None, Some(&(_, r))) => r,
+            (None, None) => unreachable!(),
+        };
+        out.push(value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reports_bad_lines_by_number() {
+        let bad = parse("1\n2\nx\n4\n\n6\n", RepairPolicy::Fail).unwrap_err();
+        assert_eq!(
+            bad,
+            vec![
+                BadLine {
+                    line: 3,
+                    text: "x".to_string()
+                },
+                BadLine {
+                    line: 5,
+                    text: "".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skip() {
+        let readings = parse("1\n2\nx\n4\n", RepairPolicy::Skip).unwrap();
+        assert_eq!(readings.values, vec![1, 2, 4]);
+        assert_eq!(readings.bad.len(), 1);
+    }
+
+    #[test]
+    fn interpolate() {
+        let readings = parse("10\n?\n?\n40\n", RepairPolicy::Interpolate).unwrap();
+        assert_eq!(readings.values, vec![10, 20, 30, 40]);
+        let readings = parse("?\n5\n?\n", RepairPolicy::Interpolate).unwrap();
+        assert_eq!(readings.values, vec![5, 5, 5]);
+        let readings = parse("40\n?\n10\n", RepairPolicy::Interpolate).unwrap();
+        assert_eq!(readings.values, vec![40, 25, 10]);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::model::{Position, SubmarineModel};
+use crate::parse::Located;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CourseError {
+    Surfaced { at: Located, depth: i64 },
+}
+
+impl fmt::Display for CourseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CourseError::Surfaced { at, depth } => write!(
+                f,
+                "line {}: `{}` takes the submarine above the surface (depth {})",
+                at.line, at.command, depth
+            ),
+        }
+    }
+}
+
+// runs the course step by step, stopping at the first command that leaves
+// the submarine in an impossible position
+pub fn run_checked(
+    model: &mut dyn SubmarineModel,
+    course: &[Located],
+) -> Result<Position, CourseError> {
+    for at in course {
+        model.step(&at.command);
+        let pos = model.position();
+        if pos.depth < 0 {
+            return Err(CourseError::Surfaced {
+                at: *at,
+                depth: pos.depth,
+            });
+        }
+    }
+    Ok(model.position())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{AimModel, SimpleModel};
+    use crate::parse::{parse_commands, ParseError};
+    use crate::SubCommand;
+
+    #[test]
+    fn rejects_unknown_commands() {
+        assert_eq!(
+            parse_commands("forward 5\n\nbackward 3\n"),
+            Err(ParseError::UnknownCommand {
+                line: 3,
+                verb: "backward".to_string()
+            })
+        );
+        assert_eq!(
+            parse_commands("down -1"),
+            Err(ParseError::BadValue {
+                line: 1,
+                value: "-1".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn catches_surfacing() {
+        let course = parse_commands("down 2\nforward 1\nup 3\nforward 4").unwrap();
+        assert_eq!(
+            run_checked(&mut SimpleModel::default(), &course),
+            Err(CourseError::Surfaced {
+                at: Located {
+                    line: 3,
+                    command: SubCommand::Up(3)
+                },
+                depth: -1
+            })
+        );
+        // under the aim model `up` only tilts the submarine; the climb
+        // happens on the following `forward`
+        assert_eq!(
+            run_checked(&mut AimModel::default(), &course),
+            Err(CourseError::Surfaced {
+                at: Located {
+                    line: 4,
+                    command: SubCommand::Forward(4)
+                },
+                depth: -2
+            })
+        );
+    }
+}
@@ -1,73 +1,132 @@
-enum SubCommand {
+mod course;
+mod model;
+mod parse;
+mod planner;
+mod trace;
+
+use model::{AimModel, ModelRegistry, SimpleModel, SubmarineModel};
+use parse::Located;
+use std::fmt;
+use trace::Trace;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubCommand {
     Forward(u32),
     Down(u32),
     Up(u32),
 }
 
+impl fmt::Display for SubCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubCommand::Forward(d) => write!(f, "forward {}", d),
+            SubCommand::Down(d) => write!(f, "down {}", d),
+            SubCommand::Up(d) => write!(f, "up {}", d),
+        }
+    }
+}
+
 fn main() {
     // let input = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2";
     let input = include_str!("input.txt");
-    let commands = input
-        .split("\n")
-        .filter_map(|line| {
-            let res = line.split(" ").collect::<Vec<_>>();
-            if res.len() == 2 {
-                let tag = res[0];
-                let value = res[1].parse::<u32>().unwrap();
-                match tag {
-                    "forward" => Some(SubCommand::Forward(value)),
-                    "down" => Some(SubCommand::Down(value)),
-                    "up" => Some(SubCommand::Up(value)),
-                    _ => None,
-                }
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<SubCommand>>();
-
-    part_1(&commands);
-    part_2(&commands);
-}
+    let course = match parse::parse_commands(input) {
+        Ok(course) => course,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let commands = course.iter().map(|at| at.command).collect::<Vec<_>>();
 
-fn part_1(commands: &[SubCommand]) {
-    let mut depth = 0;
-    let mut horiz = 0;
-    for command in commands {
-        match command {
-            SubCommand::Forward(d) => {
-                horiz += d;
+    // `cargo run -- model <name>` runs a single model and prints its state
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let registry = ModelRegistry::with_builtins();
+    let model_arg = args.get(1).map(|s| s.as_str()).unwrap_or("aim");
+    match args.first().map(|s| s.as_str()) {
+        Some("model") => {
+            let mut model = create_model(&registry, model_arg);
+            model.run(&commands);
+            println!("model: {}", model.name());
+            for (field, value) in model.state() {
+                println!("{}: {}", field, value);
+            }
+            return;
+        }
+        // `cargo run -- trace <name> [csv|json|plot]` records every step
+        Some("trace") => {
+            let mut model = create_model(&registry, model_arg);
+            let trace = Trace::record(model.as_mut(), &commands);
+            match args.get(2).map(|s| s.as_str()) {
+                None | Some("csv") => print!("{}", trace.to_csv()),
+                Some("json") => println!("{}", trace.to_json()),
+                Some("plot") => print!("{}", trace.plot(72, 20)),
+                Some(format) => panic!("unknown trace format {}", format),
+            }
+            return;
+        }
+        // `cargo run -- check` runs the course through every model
+        Some("check") => {
+            for name in registry.names() {
+                let mut model = create_model(&registry, name);
+                match course::run_checked(model.as_mut(), &course) {
+                    Ok(pos) => {
+                        println!("{}: ok, horiz {} depth {}", name, pos.horiz, pos.depth)
+                    }
+                    Err(err) => println!("{}: {}", name, err),
+                }
             }
-            SubCommand::Down(d) => {
-                depth += d;
+            return;
+        }
+        // `cargo run -- plan <simple|aim> <horiz> <depth>` prints a route file
+        Some("plan") => {
+            let target = args[2..]
+                .iter()
+                .map(|arg| arg.parse::<u32>().expect("target must be a u32"))
+                .collect::<Vec<_>>();
+            if target.len() != 2 {
+                panic!("expected a target horiz and depth");
             }
-            SubCommand::Up(d) => {
-                depth -= d;
+            match planner::plan(model_arg, target[0], target[1]) {
+                Ok(plan) => {
+                    for command in plan {
+                        println!("{}", command);
+                    }
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
             }
+            return;
         }
+        _ => {}
     }
 
-    println!("part 1: {}", depth * horiz);
+    part_1(&course);
+    part_2(&course);
 }
 
-fn part_2(commands: &[SubCommand]) {
-    let mut depth = 0;
-    let mut horiz = 0;
-    let mut aim = 0;
-    for command in commands {
-        match command {
-            SubCommand::Forward(d) => {
-                horiz += d;
-                depth += aim * d;
-            }
-            SubCommand::Down(d) => {
-                aim += d;
-            }
-            SubCommand::Up(d) => {
-                aim -= d;
-            }
-        }
+fn create_model(registry: &ModelRegistry, name: &str) -> Box<dyn SubmarineModel> {
+    match registry.create(name) {
+        Some(model) => model,
+        None => panic!(
+            "unknown model {}, expected one of {:?}",
+            name,
+            registry.names()
+        ),
     }
+}
 
-    println!("part 1: {}", depth * horiz);
+fn part_1(course: &[Located]) {
+    match course::run_checked(&mut SimpleModel::default(), course) {
+        Ok(pos) => println!("part 1: {}", pos.depth * pos.horiz),
+        Err(err) => println!("part 1: {}", err),
+    }
+}
+
+fn part_2(course: &[Located]) {
+    match course::run_checked(&mut AimModel::default(), course) {
+        Ok(pos) => println!("part 2: {}", pos.depth * pos.horiz),
+        Err(err) => println!("part 2: {}", err),
+    }
 }
@@ -0,0 +1,198 @@
+use crate::SubCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    pub horiz: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+pub trait SubmarineModel {
+    fn name(&self) -> &'static str;
+    fn step(&mut self, command: &SubCommand);
+    fn position(&self) -> Position;
+
+    // every field the model tracks, including any beyond position
+    fn state(&self) -> Vec<(&'static str, i64)> {
+        let pos = self.position();
+        vec![("horiz", pos.horiz), ("depth", pos.depth), ("aim", pos.aim)]
+    }
+
+    fn run(&mut self, commands: &[SubCommand]) {
+        for command in commands {
+            self.step(command);
+        }
+    }
+}
+
+// part 1: `down` and `up` move the submarine directly
+#[derive(Debug, Default)]
+pub struct SimpleModel {
+    pos: Position,
+}
+
+impl SubmarineModel for SimpleModel {
+    fn name(&self) -> &'static str {
+        "simple"
+    }
+    fn step(&mut self, command: &SubCommand) {
+        match *command {
+            SubCommand::Forward(d) => self.pos.horiz += d as i64,
+            SubCommand::Down(d) => self.pos.depth += d as i64,
+            SubCommand::Up(d) => self.pos.depth -= d as i64,
+        }
+    }
+    fn position(&self) -> Position {
+        self.pos
+    }
+    fn state(&self) -> Vec<(&'static str, i64)> {
+        vec![("horiz", self.pos.horiz), ("depth", self.pos.depth)]
+    }
+}
+
+// part 2: `down` and `up` change aim, and `forward` dives along it
+#[derive(Debug, Default)]
+pub struct AimModel {
+    pos: Position,
+}
+
+impl SubmarineModel for AimModel {
+    fn name(&self) -> &'static str {
+        "aim"
+    }
+    fn step(&mut self, command: &SubCommand) {
+        match *command {
+            SubCommand::Forward(d) => {
+                self.pos.horiz += d as i64;
+                self.pos.depth += self.pos.aim * d as i64;
+            }
+            SubCommand::Down(d) => self.pos.aim += d as i64,
+            SubCommand::Up(d) => self.pos.aim -= d as i64,
+        }
+    }
+    fn position(&self) -> Position {
+        self.pos
+    }
+}
+
+// aim model where a current pushes the submarine down by `drift` for every
+// unit it moves forward
+#[derive(Debug)]
+pub struct DriftModel {
+    pos: Position,
+    drift: i64,
+}
+
+impl DriftModel {
+    pub fn new(drift: i64) -> DriftModel {
+        DriftModel {
+            pos: Position::default(),
+            drift,
+        }
+    }
+}
+
+impl SubmarineModel for DriftModel {
+    fn name(&self) -> &'static str {
+        "drift"
+    }
+    fn step(&mut self, command: &SubCommand) {
+        match *command {
+            SubCommand::Forward(d) => {
+                self.pos.horiz += d as i64;
+                self.pos.depth += (self.pos.aim + self.drift) * d as i64;
+            }
+            SubCommand::Down(d) => self.pos.aim += d as i64,
+            SubCommand::Up(d) => self.pos.aim -= d as i64,
+        }
+    }
+    fn position(&self) -> Position {
+        self.pos
+    }
+    fn state(&self) -> Vec<(&'static str, i64)> {
+        vec![
+            ("horiz", self.pos.horiz),
+            ("depth", self.pos.depth),
+            ("aim", self.pos.aim),
+            ("drift", self.drift),
+        ]
+    }
+}
+
+pub type ModelFactory = fn() -> Box<dyn SubmarineModel>;
+
+#[derive(Default)]
+pub struct ModelRegistry {
+    models: Vec<(&'static str, ModelFactory)>,
+}
+
+impl ModelRegistry {
+    pub fn with_builtins() -> ModelRegistry {
+        let mut registry = ModelRegistry::default();
+        registry.register("simple", || Box::new(SimpleModel::default()));
+        registry.register("aim", || Box::new(AimModel::default()));
+        registry.register("drift", || Box::new(DriftModel::new(1)));
+        registry
+    }
+
+    // a later registration under the same name replaces the earlier one
+    pub fn register(&mut self, name: &'static str, factory: ModelFactory) {
+        self.models.retain(|(n, _)| *n != name);
+        self.models.push((name, factory));
+    }
+
+    pub fn create(&self, name: &str) -> Option<Box<dyn SubmarineModel>> {
+        self.models
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, factory)| factory())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.models.iter().map(|(n, _)| *n).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        let mut registry = ModelRegistry::with_builtins();
+        assert_eq!(registry.names(), vec!["simple", "aim", "drift"]);
+        assert!(registry.create("backwards").is_none());
+        assert_eq!(registry.create("aim").unwrap().name(), "aim");
+
+        registry.register("aim", || Box::new(DriftModel::new(5)));
+        assert_eq!(registry.names(), vec!["simple", "drift", "aim"]);
+        let mut model = registry.create("aim").unwrap();
+        assert_eq!(model.name(), "drift");
+        model.run(&[SubCommand::Forward(2)]);
+        assert_eq!(model.position().depth, 10);
+    }
+
+    #[test]
+    fn drift() {
+        let mut model = DriftModel::new(1);
+        model.run(&[
+            SubCommand::Down(2),
+            SubCommand::Forward(3),
+            SubCommand::Up(5),
+            SubCommand::Forward(1),
+        ]);
+        assert_eq!(
+            model.position(),
+            Position {
+                horiz: 4,
+                depth: 7,
+                aim: -3
+            }
+        );
+        assert_eq!(
+            model.state(),
+            vec![("horiz", 4), ("depth", 7), ("aim", -3), ("drift", 1)]
+        );
+        assert_eq!(SimpleModel::default().state().len(), 2);
+    }
+}
@@ -0,0 +1,437 @@
+use crate::SubCommand;
+use std::collections::HashMap;
+use std::fmt;
+
+// Route files are a list of items, one command per line as before, plus:
+//
+//   # comment to the end of the line
+//   macro dive { down 5 forward 2 }
+//   repeat 3 { dive forward 1 }
+//
+// Macros must be defined at the top level before they are used, so they
+// cannot recurse. Everything is expanded into a flat command list carrying
+// the source line of each primitive command.
+
+const MAX_COMMANDS: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Located {
+    pub line: usize,
+    pub command: SubCommand,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    UnknownCommand {
+        line: usize,
+        verb: String,
+    },
+    BadValue {
+        line: usize,
+        value: String,
+    },
+    Malformed {
+        line: usize,
+        text: String,
+    },
+    Unexpected {
+        line: usize,
+        found: String,
+        expected: &'static str,
+    },
+    Unclosed {
+        line: usize,
+    },
+    DuplicateMacro {
+        line: usize,
+        name: String,
+    },
+    TooLong {
+        line: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::UnknownCommand { line, verb } => {
+                write!(f, "line {}: unknown command {:?}", line, verb)
+            }
+            ParseError::BadValue { line, value } => {
+                write!(f, "line {}: bad value {:?}", line, value)
+            }
+            ParseError::Malformed { line, text } => {
+                write!(
+                    f,
+                    "line {}: expected `<command> <value>`, got {:?}",
+                    line, text
+                )
+            }
+            ParseError::Unexpected {
+                line,
+                found,
+                expected,
+            } => write!(f, "line {}: expected {}, got {:?}", line, expected, found),
+            ParseError::Unclosed { line } => {
+                write!(f, "line {}: block is never closed", line)
+            }
+            ParseError::DuplicateMacro { line, name } => {
+                write!(f, "line {}: macro {:?} is already defined", line, name)
+            }
+            ParseError::TooLong { line } => write!(
+                f,
+                "line {}: course expands to more than {} commands",
+                line, MAX_COMMANDS
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token<'a> {
+    line: usize,
+    text: &'a str,
+}
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for (i, text) in input.split("\n").enumerate() {
+        let text = match text.find('#') {
+            Some(idx) => &text[..idx],
+            None => text,
+        };
+        let mut start = None;
+        for (idx, ch) in text.char_indices() {
+            if ch.is_whitespace() || ch == '{' || ch == '}' {
+                if let Some(s) = start.take() {
+                    tokens.push(Token {
+                        line: i + 1,
+                        text: &text[s..idx],
+                    });
+                }
+                if !ch.is_whitespace() {
+                    tokens.push(Token {
+                        line: i + 1,
+                        text: &text[idx..idx + 1],
+                    });
+                }
+            } else if start.is_none() {
+                start = Some(idx);
+            }
+        }
+        if let Some(s) = start {
+            tokens.push(Token {
+                line: i + 1,
+                text: &text[s..],
+            });
+        }
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    Command(Located),
+    Repeat {
+        line: usize,
+        count: u32,
+        body: Vec<Item>,
+    },
+    Call {
+        line: usize,
+        name: String,
+    },
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    index: usize,
+    macros: HashMap<String, Vec<Item>>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.index)
+    }
+
+    fn advance(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn parse_program(&mut self) -> Result<Vec<Item>, ParseError> {
+        let mut items = Vec::new();
+        while let Some(token) = self.peek() {
+            if token.text == "macro" {
+                self.parse_macro()?;
+            } else {
+                items.push(self.parse_item()?);
+            }
+        }
+        Ok(items)
+    }
+
+    fn parse_macro(&mut self) -> Result<(), ParseError> {
+        let keyword = self.advance().unwrap();
+        let name = self.expect_word(keyword.line, "a macro name")?;
+        if is_keyword(name.text) || name.text.parse::<u32>().is_ok() {
+            return Err(ParseError::Unexpected {
+                line: name.line,
+                found: name.text.to_string(),
+                expected: "a macro name",
+            });
+        }
+        if self.macros.contains_key(name.text) {
+            return Err(ParseError::DuplicateMacro {
+                line: name.line,
+                name: name.text.to_string(),
+            });
+        }
+        let body = self.parse_block(keyword.line)?;
+        self.macros.insert(name.text.to_string(), body);
+        Ok(())
+    }
+
+    fn parse_block(&mut self, open_line: usize) -> Result<Vec<Item>, ParseError> {
+        let open = self.expect_word(open_line, "`{`")?;
+        if open.text != "{" {
+            return Err(ParseError::Unexpected {
+                line: open.line,
+                found: open.text.to_string(),
+                expected: "`{`",
+            });
+        }
+        let mut body = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(ParseError::Unclosed { line: open.line }),
+                Some(token) if token.text == "}" => {
+                    self.advance();
+                    return Ok(body);
+                }
+                Some(token) if token.text == "macro" => {
+                    return Err(ParseError::Unexpected {
+                        line: token.line,
+                        found: token.text.to_string(),
+                        expected: "a command (macros are top-level only)",
+                    })
+                }
+                Some(_) => body.push(self.parse_item()?),
+            }
+        }
+    }
+
+    fn parse_item(&mut self) -> Result<Item, ParseError> {
+        let token = self.advance().unwrap();
+        match token.text {
+            "forward" | "down" | "up" => {
+                let value = match self.peek() {
+                    Some(next) if next.line == token.line && !is_brace(next.text) => {
+                        self.advance().unwrap()
+                    }
+                    _ => {
+                        return Err(ParseError::Malformed {
+                            line: token.line,
+                            text: token.text.to_string(),
+                        })
+                    }
+                };
+                let value = parse_value(&value)?;
+                let command = match token.text {
+                    "forward" => SubCommand::Forward(value),
+                    "down" => SubCommand::Down(value),
+                    _ => SubCommand::Up(value),
+                };
+                Ok(Item::Command(Located {
+                    line: token.line,
+                    command,
+                }))
+            }
+            "repeat" => {
+                let count = self.expect_word(token.line, "a repeat count")?;
+                let count = parse_value(&count)?;
+                let body = self.parse_block(token.line)?;
+                Ok(Item::Repeat {
+                    line: token.line,
+                    count,
+                    body,
+                })
+            }
+            "{" | "}" => Err(ParseError::Unexpected {
+                line: token.line,
+                found: token.text.to_string(),
+                expected: "a command",
+            }),
+            name if self.macros.contains_key(name) => Ok(Item::Call {
+                line: token.line,
+                name: name.to_string(),
+            }),
+            verb => Err(ParseError::UnknownCommand {
+                line: token.line,
+                verb: verb.to_string(),
+            }),
+        }
+    }
+
+    fn expect_word(
+        &mut self,
+        line: usize,
+        expected: &'static str,
+    ) -> Result<Token<'a>, ParseError> {
+        match self.advance() {
+            Some(token) => Ok(token),
+            None => Err(ParseError::Unexpected {
+                line,
+                found: "end of input".to_string(),
+                expected,
+            }),
+        }
+    }
+
+    fn expand(&self, items: &[Item], out: &mut Vec<Located>) -> Result<(), ParseError> {
+        for item in items {
+            match item {
+                Item::Command(at) => {
+                    if out.len() >= MAX_COMMANDS {
+                        return Err(ParseError::TooLong { line: at.line });
+                    }
+                    out.push(*at);
+                }
+                Item::Repeat { line, count, body } => {
+                    for _ in 0..*count {
+                        let before = out.len();
+                        self.expand(body, out)?;
+                        // every pass adds the same; an empty body never ends
+                        // up adding anything, however many times it repeats
+                        if out.len() == before {
+                            break;
+                        }
+                        if out.len() >= MAX_COMMANDS {
+                            return Err(ParseError::TooLong { line: *line });
+                        }
+                    }
+                }
+                Item::Call { name, .. } => self.expand(&self.macros[name], out)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_keyword(text: &str) -> bool {
+    matches!(text, "forward" | "down" | "up" | "repeat" | "macro") || is_brace(text)
+}
+
+fn is_brace(text: &str) -> bool {
+    text == "{" || text == "}"
+}
+
+fn parse_value(token: &Token) -> Result<u32, ParseError> {
+    token.text.parse::<u32>().map_err(|_| ParseError::BadValue {
+        line: token.line,
+        value: token.text.to_string(),
+    })
+}
+
+pub fn parse_commands(input: &str) -> Result<Vec<Located>, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(input),
+        index: 0,
+        macros: HashMap::new(),
+    };
+    let program = parser.parse_program()?;
+    let mut out = Vec::new();
+    parser.expand(&program, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn commands(input: &str) -> Vec<SubCommand> {
+        parse_commands(input)
+            .unwrap()
+            .into_iter()
+            .map(|at| at.command)
+            .collect()
+    }
+
+    #[test]
+    fn plain_commands() {
+        assert_eq!(
+            commands("forward 5\ndown 5\nforward 8\nup 3\n"),
+            vec![
+                SubCommand::Forward(5),
+                SubCommand::Down(5),
+                SubCommand::Forward(8),
+                SubCommand::Up(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn repeat_macros_and_comments() {
+        let input = "# descend in steps\nmacro step {\n  down 1 # tilt\n  forward 2\n}\nrepeat 2 { step }\nup 2";
+        let course = parse_commands(input).unwrap();
+        assert_eq!(
+            course.iter().map(|at| at.command).collect::<Vec<_>>(),
+            vec![
+                SubCommand::Down(1),
+                SubCommand::Forward(2),
+                SubCommand::Down(1),
+                SubCommand::Forward(2),
+                SubCommand::Up(2)
+            ]
+        );
+        assert_eq!(
+            course.iter().map(|at| at.line).collect::<Vec<_>>(),
+            vec![3, 4, 3, 4, 7]
+        );
+        assert_eq!(
+            commands("repeat 2 { repeat 2 { forward 1 } }"),
+            vec![SubCommand::Forward(1); 4]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_commands("forward 1\nrepeat 2 {\n  up 1\n"),
+            Err(ParseError::Unclosed { line: 2 })
+        );
+        assert_eq!(
+            parse_commands("forward\n5"),
+            Err(ParseError::Malformed {
+                line: 1,
+                text: "forward".to_string()
+            })
+        );
+        assert_eq!(
+            parse_commands("macro a { a }"),
+            Err(ParseError::UnknownCommand {
+                line: 1,
+                verb: "a".to_string()
+            })
+        );
+        assert_eq!(
+            parse_commands("macro a { up 1 }\nmacro a { up 2 }"),
+            Err(ParseError::DuplicateMacro {
+                line: 2,
+                name: "a".to_string()
+            })
+        );
+        assert_eq!(
+            parse_commands("repeat 1000 { repeat 1000 { repeat 1000 { up 1 } } }"),
+            Err(ParseError::TooLong { line: 1 })
+        );
+        assert_eq!(
+            parse_commands(
+                "macro nothing { }\nrepeat 4294967295 { repeat 4294967295 { nothing } }"
+            ),
+            Ok(vec![])
+        );
+        assert_eq!(parse_commands("repeat 4294967295 { }"), Ok(vec![]));
+    }
+}
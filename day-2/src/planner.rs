@@ -0,0 +1,186 @@
+use crate::course;
+use crate::model::{AimModel, SimpleModel, SubmarineModel};
+use crate::parse::Located;
+use crate::SubCommand;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanError {
+    UnknownModel(String),
+    Unreachable { horiz: u32, depth: u32 },
+    Verify(String),
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanError::UnknownModel(name) => write!(f, "no planner for model {:?}", name),
+            PlanError::Unreachable { horiz, depth } => write!(
+                f,
+                "horiz {} depth {} cannot be reached with this model",
+                horiz, depth
+            ),
+            PlanError::Verify(reason) => write!(f, "plan failed verification: {}", reason),
+        }
+    }
+}
+
+// Shortest course to the target, replayed through the checked interpreter
+// before it is returned.
+pub fn plan(model: &str, horiz: u32, depth: u32) -> Result<Vec<SubCommand>, PlanError> {
+    let (commands, mut sub): (_, Box<dyn SubmarineModel>) = match model {
+        "simple" => (plan_simple(horiz, depth), Box::new(SimpleModel::default())),
+        "aim" => (plan_aim(horiz, depth)?, Box::new(AimModel::default())),
+        name => return Err(PlanError::UnknownModel(name.to_string())),
+    };
+
+    let course = commands
+        .iter()
+        .enumerate()
+        .map(|(i, command)| Located {
+            line: i + 1,
+            command: *command,
+        })
+        .collect::<Vec<_>>();
+    let pos = course::run_checked(sub.as_mut(), &course)
+        .map_err(|err| PlanError::Verify(err.to_string()))?;
+    if pos.horiz != horiz as i64 || pos.depth != depth as i64 {
+        return Err(PlanError::Verify(format!(
+            "ended at horiz {} depth {}",
+            pos.horiz, pos.depth
+        )));
+    }
+    Ok(commands)
+}
+
+// each axis needs its own command, and one is enough
+fn plan_simple(horiz: u32, depth: u32) -> Vec<SubCommand> {
+    let mut out = Vec::new();
+    if horiz > 0 {
+        out.push(SubCommand::Forward(horiz));
+    }
+    if depth > 0 {
+        out.push(SubCommand::Down(depth));
+    }
+    out
+}
+
+// Depth only changes on `forward`, by aim * distance. One command can only
+// reach depth 0; two (`down a`, `forward h`) need a * h == depth. Otherwise
+// cruise level for `h - y` and dive the last `y` units, where y is the largest
+// divisor of depth below h, which always exists when h > 1.
+fn plan_aim(horiz: u32, depth: u32) -> Result<Vec<SubCommand>, PlanError> {
+    if depth == 0 {
+        return Ok(plan_simple(horiz, 0));
+    }
+    if horiz == 0 {
+        return Err(PlanError::Unreachable { horiz, depth });
+    }
+    if depth.is_multiple_of(horiz) {
+        return Ok(vec![
+            SubCommand::Down(depth / horiz),
+            SubCommand::Forward(horiz),
+        ]);
+    }
+    let y = largest_divisor_below(depth, horiz).ok_or(PlanError::Unreachable { horiz, depth })?;
+    Ok(vec![
+        SubCommand::Forward(horiz - y),
+        SubCommand::Down(depth / y),
+        SubCommand::Forward(y),
+    ])
+}
+
+// walks divisor pairs up to sqrt(n), so it's quick even for a prime n
+fn largest_divisor_below(n: u32, limit: u32) -> Option<u32> {
+    let mut best = None;
+    let mut a = 1u32;
+    while (a as u64) * (a as u64) <= n as u64 {
+        if n.is_multiple_of(a) {
+            for y in [a, n / a] {
+                if y < limit && best.is_none_or(|b| y > b) {
+                    best = Some(y);
+                }
+            }
+        }
+        a += 1;
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn reaches(model: &mut dyn SubmarineModel, commands: &[SubCommand], h: u32, d: u32) -> bool {
+        model.run(commands);
+        let pos = model.position();
+        pos.horiz == h as i64 && pos.depth == d as i64
+    }
+
+    // shortest sequence found by trying everything up to length 2
+    fn brute_force(model: &str, h: u32, d: u32) -> Option<usize> {
+        let fresh = || -> Box<dyn SubmarineModel> {
+            match model {
+                "simple" => Box::new(SimpleModel::default()),
+                _ => Box::new(AimModel::default()),
+            }
+        };
+        let mut single = vec![];
+        for v in 1..=12 {
+            single.push(SubCommand::Forward(v));
+            single.push(SubCommand::Down(v));
+            single.push(SubCommand::Up(v));
+        }
+        if reaches(fresh().as_mut(), &[], h, d) {
+            return Some(0);
+        }
+        if single
+            .iter()
+            .any(|c| reaches(fresh().as_mut(), &[*c], h, d))
+        {
+            return Some(1);
+        }
+        for a in &single {
+            for b in &single {
+                if reaches(fresh().as_mut(), &[*a, *b], h, d) {
+                    return Some(2);
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn plans_are_minimal() {
+        for model in ["simple", "aim"] {
+            for h in 0..=12 {
+                for d in 0..=12 {
+                    match plan(model, h, d) {
+                        Ok(commands) => match brute_force(model, h, d) {
+                            Some(len) => assert_eq!(commands.len(), len, "{} {} {}", model, h, d),
+                            None => assert_eq!(commands.len(), 3, "{} {} {}", model, h, d),
+                        },
+                        Err(err) => {
+                            assert_eq!(err, PlanError::Unreachable { horiz: h, depth: d });
+                            assert_eq!((model, h, brute_force(model, h, d)), ("aim", 0, None));
+                        }
+                    }
+                }
+            }
+        }
+
+        // the largest targets, including a prime depth just under u32::MAX
+        for (h, d) in [(2, u32::MAX), (u32::MAX, u32::MAX), (u32::MAX, 4294967291)] {
+            assert!(plan("aim", h, d).unwrap().len() <= 3, "{} {}", h, d);
+            assert_eq!(plan("simple", h, d).unwrap().len(), 2);
+        }
+        assert_eq!(
+            plan("aim", 2, u32::MAX),
+            Ok(vec![
+                SubCommand::Forward(1),
+                SubCommand::Down(u32::MAX),
+                SubCommand::Forward(1)
+            ])
+        );
+    }
+}
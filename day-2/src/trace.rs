@@ -0,0 +1,163 @@
+use crate::model::{Position, SubmarineModel};
+use crate::SubCommand;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceRow {
+    pub step: usize,
+    pub command: SubCommand,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub model: &'static str,
+    pub start: Position,
+    pub rows: Vec<TraceRow>,
+}
+
+impl Trace {
+    pub fn record(model: &mut dyn SubmarineModel, commands: &[SubCommand]) -> Trace {
+        let start = model.position();
+        let rows = commands
+            .iter()
+            .enumerate()
+            .map(|(i, command)| {
+                model.step(command);
+                TraceRow {
+                    step: i + 1,
+                    command: *command,
+                    pos: model.position(),
+                }
+            })
+            .collect();
+        Trace {
+            model: model.name(),
+            start,
+            rows,
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("step,command,horiz,depth,aim\n");
+        for row in &self.rows {
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                row.step, row.command, row.pos.horiz, row.pos.depth, row.pos.aim
+            ));
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                format!(
+                    "{{\"step\":{},\"command\":\"{}\",\"horiz\":{},\"depth\":{},\"aim\":{}}}",
+                    row.step, row.command, row.pos.horiz, row.pos.depth, row.pos.aim
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{\"model\":\"{}\",\"trace\":[{}]}}", self.model, rows)
+    }
+
+    // side view with horizontal position left to right and depth increasing
+    // downwards; `~` marks the surface when it is in view
+    pub fn plot(&self, width: usize, height: usize) -> String {
+        let points = std::iter::once(self.start)
+            .chain(self.rows.iter().map(|row| row.pos))
+            .collect::<Vec<_>>();
+        let min_h = points.iter().map(|p| p.horiz).min().unwrap();
+        let max_h = points.iter().map(|p| p.horiz).max().unwrap();
+        let min_d = points.iter().map(|p| p.depth).min().unwrap().min(0);
+        let max_d = points.iter().map(|p| p.depth).max().unwrap();
+        let scale = |value: i64, min: i64, max: i64, size: usize| -> usize {
+            if max == min {
+                0
+            } else {
+                ((value - min) as f64 / (max - min) as f64 * (size - 1) as f64).round() as usize
+            }
+        };
+
+        let mut grid = vec![vec![' '; width]; height];
+        if min_d <= 0 {
+            let row = scale(0, min_d, max_d, height);
+            for cell in grid[row].iter_mut() {
+                *cell = '~';
+            }
+        }
+        for pair in points.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let x0 = scale(from.horiz, min_h, max_h, width) as f64;
+            let y0 = scale(from.depth, min_d, max_d, height) as f64;
+            let x1 = scale(to.horiz, min_h, max_h, width) as f64;
+            let y1 = scale(to.depth, min_d, max_d, height) as f64;
+            let steps = (x1 - x0).abs().max((y1 - y0).abs()).max(1.0) as usize;
+            for i in 0..=steps {
+                let t = i as f64 / steps as f64;
+                let x = (x0 + (x1 - x0) * t).round() as usize;
+                let y = (y0 + (y1 - y0) * t).round() as usize;
+                grid[y][x] = '*';
+            }
+        }
+
+        let mut out = format!(
+            "depth {} .. {}, horiz {} .. {}\n",
+            min_d, max_d, min_h, max_h
+        );
+        for row in grid {
+            out.push('|');
+            out.extend(row);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::{AimModel, SimpleModel};
+
+    #[test]
+    fn rows() {
+        let commands = [SubCommand::Down(5), SubCommand::Forward(2)];
+        let trace = Trace::record(&mut AimModel::default(), &commands);
+        assert_eq!(
+            trace.to_csv(),
+            "step,command,horiz,depth,aim\n1,down 5,0,0,5\n2,forward 2,2,10,5\n"
+        );
+        assert_eq!(
+            trace.to_json(),
+            "{\"model\":\"aim\",\"trace\":[\
+             {\"step\":1,\"command\":\"down 5\",\"horiz\":0,\"depth\":0,\"aim\":5},\
+             {\"step\":2,\"command\":\"forward 2\",\"horiz\":2,\"depth\":10,\"aim\":5}]}"
+        );
+    }
+
+    #[test]
+    fn flat_course() {
+        let trace = Trace::record(&mut SimpleModel::default(), &[SubCommand::Forward(0)]);
+        assert_eq!(
+            trace.plot(4, 3),
+            "depth 0 .. 0, horiz 0 .. 0\n|*~~~\n|    \n|    \n"
+        );
+    }
+
+    #[test]
+    fn crosses_the_surface() {
+        let commands = [
+            SubCommand::Down(2),
+            SubCommand::Forward(2),
+            SubCommand::Up(4),
+            SubCommand::Forward(2),
+        ];
+        let trace = Trace::record(&mut SimpleModel::default(), &commands);
+        assert_eq!(
+            trace.plot(5, 5),
+            "depth -2 .. 2, horiz 0 .. 4\n|  ***\n|  *  \n|*~*~~\n|* *  \n|***  \n"
+        );
+    }
+}
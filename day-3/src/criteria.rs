@@ -0,0 +1,151 @@
+use crate::packed::Report;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prefer {
+    Majority,
+    Minority,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TieBreak {
+    Zero,
+    One,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitCriteria {
+    pub prefer: Prefer,
+    pub tie: TieBreak,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tie {
+    pub col: usize,
+}
+
+impl fmt::Display for Tie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ones and zeroes are tied in column {}", self.col)
+    }
+}
+
+impl BitCriteria {
+    // part 1 only ever sets a gamma bit on a strict majority, so ties go to 0
+    // in gamma and to 1 in its complement
+    pub const GAMMA: BitCriteria = BitCriteria {
+        prefer: Prefer::Majority,
+        tie: TieBreak::Zero,
+    };
+    pub const EPSILON: BitCriteria = BitCriteria {
+        prefer: Prefer::Minority,
+        tie: TieBreak::One,
+    };
+    // part 2 spells its tie rules out: oxygen keeps 1s, CO2 keeps 0s
+    pub const OXYGEN: BitCriteria = BitCriteria {
+        prefer: Prefer::Majority,
+        tie: TieBreak::One,
+    };
+    pub const CO2: BitCriteria = BitCriteria {
+        prefer: Prefer::Minority,
+        tie: TieBreak::Zero,
+    };
+
+    pub fn parse(prefer: &str, tie: &str) -> Option<BitCriteria> {
+        let prefer = match prefer {
+            "majority" => Prefer::Majority,
+            "minority" => Prefer::Minority,
+            _ => return None,
+        };
+        let tie = match tie {
+            "0" => TieBreak::Zero,
+            "1" => TieBreak::One,
+            "error" => TieBreak::Error,
+            _ => return None,
+        };
+        Some(BitCriteria { prefer, tie })
+    }
+
+    // the bit this criteria picks given the weight behind each value
+    pub fn pick(&self, ones: u64, zeroes: u64, col: usize) -> Result<bool, Tie> {
+        if ones == zeroes {
+            return match self.tie {
+                TieBreak::Zero => Ok(false),
+                TieBreak::One => Ok(true),
+                TieBreak::Error => Err(Tie { col }),
+            };
+        }
+        Ok((ones > zeroes) == (self.prefer == Prefer::Majority))
+    }
+}
+
+// every column decided independently over the whole report, as in part 1
+pub fn column_rating(report: &Report, criteria: BitCriteria) -> Result<u128, Tie> {
+    let total = report.total_weight();
+    let mut out = 0;
+    for (col, ones) in report.column_weights().into_iter().enumerate() {
+        if criteria.pick(ones, total - ones, col)? {
+            out |= report.mask(col);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::index::PrefixIndex;
+
+    // odd count: column 0 has 2 of 3 ones, column 1 has 1 of 3
+    const ODD: &str = "10\n11\n00";
+    // even count: column 0 is tied 2-2, column 1 has 3 of 4 ones
+    const EVEN: &str = "11\n11\n01\n00";
+
+    #[test]
+    fn pick() {
+        let majority = BitCriteria::parse("majority", "error").unwrap();
+        assert_eq!(majority.pick(3, 2, 0), Ok(true));
+        assert_eq!(majority.pick(2, 3, 0), Ok(false));
+        assert_eq!(majority.pick(2, 2, 4), Err(Tie { col: 4 }));
+        assert_eq!(BitCriteria::CO2.pick(3, 2, 0), Ok(false));
+        assert_eq!(BitCriteria::CO2.pick(2, 2, 0), Ok(false));
+        assert_eq!(BitCriteria::OXYGEN.pick(2, 2, 0), Ok(true));
+    }
+
+    #[test]
+    fn part_1_odd_and_even() {
+        let odd = Report::parse(ODD);
+        assert_eq!(column_rating(&odd, BitCriteria::GAMMA), Ok(0b10));
+        assert_eq!(column_rating(&odd, BitCriteria::EPSILON), Ok(0b01));
+
+        let even = Report::parse(EVEN);
+        assert_eq!(column_rating(&even, BitCriteria::GAMMA), Ok(0b01));
+        assert_eq!(column_rating(&even, BitCriteria::EPSILON), Ok(0b10));
+        let strict = BitCriteria::parse("majority", "error").unwrap();
+        assert_eq!(column_rating(&even, strict), Err(Tie { col: 0 }));
+    }
+
+    #[test]
+    fn part_2_odd_and_even() {
+        let odd = PrefixIndex::new(&Report::parse(ODD));
+        assert_eq!(odd.rating(BitCriteria::OXYGEN), Ok(0b11));
+        assert_eq!(odd.rating(BitCriteria::CO2), Ok(0b00));
+
+        let even = PrefixIndex::new(&Report::parse(EVEN));
+        assert_eq!(even.rating(BitCriteria::OXYGEN), Ok(0b11));
+        assert_eq!(even.rating(BitCriteria::CO2), Ok(0b00));
+        let strict = BitCriteria::parse("minority", "error").unwrap();
+        assert_eq!(even.rating(strict), Err(Tie { col: 0 }));
+    }
+
+    #[test]
+    fn weighted_rows() {
+        // the single 0 row outweighs both 1 rows
+        let report = Report::parse("1\n1\n0 3");
+        assert_eq!(column_rating(&report, BitCriteria::GAMMA), Ok(0));
+        let index = PrefixIndex::new(&report);
+        assert_eq!(index.rating(BitCriteria::OXYGEN), Ok(0));
+        assert_eq!(index.rating(BitCriteria::CO2), Ok(1));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::criteria::{BitCriteria, Tie};
+use crate::packed::Report;
+use std::ops::Range;
+
+// Rows sorted once; every set of rows sharing a prefix is then a contiguous
+// range, and splitting it on the next column is a binary search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixIndex {
+    sorted: Vec<u128>,
+    // running total of row weights, so any range's weight is one subtraction
+    cumulative: Vec<u64>,
+    width: usize,
+}
+
+impl PrefixIndex {
+    pub fn new(report: &Report) -> PrefixIndex {
+        let mut pairs = (0..report.rows.len())
+            .map(|i| (report.rows[i], report.weight(i)))
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        let mut cumulative = Vec::with_capacity(pairs.len() + 1);
+        cumulative.push(0);
+        for (_, weight) in &pairs {
+            cumulative.push(cumulative.last().unwrap() + weight);
+        }
+        PrefixIndex {
+            sorted: pairs.into_iter().map(|(row, _)| row).collect(),
+            cumulative,
+            width: report.width,
+        }
+    }
+
+    fn weight(&self, range: &Range<usize>) -> u64 {
+        self.cumulative[range.end] - self.cumulative[range.start]
+    }
+
+    fn mask(&self, col: usize) -> u128 {
+        1 << (self.width - 1 - col)
+    }
+
+    // rows in `range` share columns 0..col, so those with a 0 in `col` come first
+    pub fn split(&self, range: Range<usize>, col: usize) -> (Range<usize>, Range<usize>) {
+        let mask = self.mask(col);
+        let mid = range.start + self.sorted[range.clone()].partition_point(|row| row & mask == 0);
+        (range.start..mid, mid..range.end)
+    }
+
+    // range of rows whose first `len` columns equal `prefix`
+    pub fn prefix_range(&self, prefix: u128, len: usize) -> Range<usize> {
+        let mut range = 0..self.sorted.len();
+        for col in 0..len {
+            let (zeroes, ones) = self.split(range, col);
+            range = if prefix >> (len - 1 - col) & 1 == 1 {
+                ones
+            } else {
+                zeroes
+            };
+        }
+        range
+    }
+
+    pub fn prefix_count(&self, prefix: u128, len: usize) -> usize {
+        self.prefix_range(prefix, len).len()
+    }
+
+    // (prefix, count) for each distinct prefix of length `len` in the report
+    pub fn prefixes(&self, len: usize) -> Vec<(u128, usize)> {
+        let shift = self.width - len;
+        let mut out: Vec<(u128, usize)> = Vec::new();
+        for row in &self.sorted {
+            let prefix = if len == 0 { 0 } else { row >> shift };
+            match out.last_mut() {
+                Some((last, count)) if *last == prefix => *count += 1,
+                _ => out.push((prefix, 1)),
+            }
+        }
+        out
+    }
+
+    // ties go to the smaller prefix
+    pub fn most_common_prefix(&self, len: usize) -> Option<(u128, usize)> {
+        self.prefixes(len)
+            .into_iter()
+            .fold(None, |best, (p, c)| match best {
+                Some((_, bc)) if bc >= c => best,
+                _ => Some((p, c)),
+            })
+    }
+
+    // least common among prefixes that occur at all; ties go to the smaller
+    pub fn least_common_prefix(&self, len: usize) -> Option<(u128, usize)> {
+        self.prefixes(len)
+            .into_iter()
+            .fold(None, |best, (p, c)| match best {
+                Some((_, bc)) if bc <= c => best,
+                _ => Some((p, c)),
+            })
+    }
+
+    // Walk down the columns keeping the half the criteria picks, stopping
+    // once a single row is left. A half with no rows is never chosen.
+    pub fn rating(&self, criteria: BitCriteria) -> Result<u128, Tie> {
+        self.filter_steps(criteria).1
+    }
+
+    // the same walk, recording the rows still in the running after each column
+    pub fn filter_steps(&self, criteria: BitCriteria) -> (Vec<FilterStep>, Result<u128, Tie>) {
+        let mut steps = Vec::new();
+        let mut range = 0..self.sorted.len();
+        for col in 0..self.width {
+            if range.len() <= 1 {
+                break;
+            }
+            let (zeroes, ones) = self.split(range, col);
+            let (ones_weight, zeroes_weight) = (self.weight(&ones), self.weight(&zeroes));
+            let pick_ones = match criteria.pick(ones_weight, zeroes_weight, col) {
+                Ok(pick_ones) => pick_ones,
+                Err(tie) => return (steps, Err(tie)),
+            };
+            range = match (pick_ones, zeroes.is_empty(), ones.is_empty()) {
+                (_, true, _) | (true, _, false) => ones,
+                _ => zeroes,
+            };
+            steps.push(FilterStep {
+                col,
+                ones: ones_weight,
+                zeroes: zeroes_weight,
+                kept: range.clone(),
+            });
+        }
+        (steps, Ok(self.sorted[range.start]))
+    }
+
+    pub fn rows(&self, range: &Range<usize>) -> &[u128] {
+        &self.sorted[range.clone()]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterStep {
+    pub col: usize,
+    pub ones: u64,
+    pub zeroes: u64,
+    // index into the sorted rows; see `PrefixIndex::rows`
+    pub kept: Range<usize>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ratings() {
+        let index = PrefixIndex::new(&Report::parse(include_str!("test-input.txt")));
+        assert_eq!(index.rating(BitCriteria::OXYGEN), Ok(0b10111));
+        assert_eq!(index.rating(BitCriteria::CO2), Ok(0b01010));
+    }
+
+    #[test]
+    fn prefix_queries() {
+        let index = PrefixIndex::new(&Report::parse(include_str!("test-input.txt")));
+        assert_eq!(index.prefix_count(0b1, 1), 7);
+        assert_eq!(index.prefix_count(0b101, 3), 3);
+        assert_eq!(index.most_common_prefix(2), Some((0b10, 4)));
+        assert_eq!(index.least_common_prefix(2), Some((0b01, 2)));
+        assert_eq!(index.prefixes(0), vec![(0, 12)]);
+    }
+}
@@ -1,84 +1,102 @@
+mod criteria;
+mod index;
+mod packed;
+mod stats;
+
+use criteria::{column_rating, BitCriteria};
+use index::PrefixIndex;
+use packed::Report;
+
 fn main() {
     let input = include_str!("input.txt");
-    let values = input
-        .split("\n")
-        .map(|line| {
-            line.chars()
-                .map(|ch| match ch {
-                    '0' => 0,
-                    '1' => 1,
-                    _ => {
-                        panic!("invalid char")
-                    }
-                })
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<_>>();
+    let report = Report::parse(input);
 
-    part_1(&values);
-    part_2(&values);
-}
-
-fn part_1(values: &[Vec<usize>]) {
-    let size = values[0].len();
-    let mut counter = vec![0 as usize; size];
-    for row in values {
-        for i in 0..size {
-            counter[i] += row[i];
+    // `cargo run -- prefix <len>` prints the most and least common prefixes
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(|s| s.as_str()) == Some("prefix") {
+        let len = args
+            .get(1)
+            .map(|s| s.parse::<usize>().expect("prefix length"))
+            .unwrap_or(1);
+        if len > report.width {
+            panic!("rows are only {} bits wide", report.width);
         }
+        let index = PrefixIndex::new(&report);
+        for (label, found) in [
+            ("most", index.most_common_prefix(len)),
+            ("least", index.least_common_prefix(len)),
+        ] {
+            if let Some((prefix, count)) = found {
+                println!(
+                    "{} common: {:0len$b} ({} rows)",
+                    label,
+                    prefix,
+                    count,
+                    len = len
+                );
+            }
+        }
+        return;
     }
-    let mut gamma = vec![0 as usize; size];
-    let mut epsilon = vec![0 as usize; size];
-    let half_length = values.len() >> 1;
-    for i in 0..size {
-        if counter[i] > half_length {
-            gamma[i] = 1;
-            epsilon[i] = 0;
-        } else {
-            gamma[i] = 0;
-            epsilon[i] = 1;
+    // `cargo run -- count <bits>` counts rows starting with the given bits
+    if args.first().map(|s| s.as_str()) == Some("count") {
+        let bits = args.get(1).expect("prefix bits");
+        let prefix = u128::from_str_radix(bits, 2).expect("prefix must be binary");
+        if bits.len() > report.width {
+            panic!("rows are only {} bits wide", report.width);
         }
+        let index = PrefixIndex::new(&report);
+        println!("{}: {} rows", bits, index.prefix_count(prefix, bits.len()));
+        return;
     }
-    println!("part 1: {}", to_int(&gamma) * to_int(&epsilon));
-}
-
-fn part_2(values: &[Vec<usize>]) {
-    let oxygen_rating = get_rating(values, true);
-    let co2_rating = get_rating(values, false);
-    println!("part 2:  {}", oxygen_rating * co2_rating);
-}
 
-fn get_rating(values: &[Vec<usize>], get_most: bool) -> usize {
-    let size = values[0].len();
-    let mut current = values.to_vec();
-    for idx in 0..size {
-        let mut ones = Vec::new();
-        let mut zeroes = Vec::new();
-        for row in &current {
-            if row[idx] == 1 {
-                ones.push(row.to_vec());
-            } else {
-                zeroes.push(row.to_vec());
-            }
+    // `cargo run -- rate <majority|minority> <0|1|error>` applies one criteria
+    // both per column (as in part 1) and by filtering (as in part 2)
+    if args.first().map(|s| s.as_str()) == Some("rate") {
+        let criteria = match (args.get(1), args.get(2)) {
+            (Some(prefer), Some(tie)) => BitCriteria::parse(prefer, tie),
+            _ => None,
         }
-        if (ones.len() >= zeroes.len()) == get_most {
-            current = ones;
-        } else {
-            current = zeroes;
+        .expect("expected <majority|minority> <0|1|error>");
+        match column_rating(&report, criteria) {
+            Ok(rating) => println!("columns: {:0w$b}", rating, w = report.width),
+            Err(tie) => println!("columns: {}", tie),
         }
-        if current.len() == 1 {
-            break;
+        match PrefixIndex::new(&report).rating(criteria) {
+            Ok(rating) => println!("filtered: {:0w$b}", rating, w = report.width),
+            Err(tie) => println!("filtered: {}", tie),
         }
+        return;
     }
-    to_int(&current[0])
-}
 
-fn to_int(row: &[usize]) -> usize {
-    let mut acc = 0;
-    for bit in row {
-        acc = acc << 1;
-        acc += bit;
+    // `cargo run -- stats` prints per-column statistics and each filter step
+    if args.first().map(|s| s.as_str()) == Some("stats") {
+        stats::print_report(&report);
+        return;
     }
 
-    acc
+    part_1(&report);
+    part_2(&report);
+}
+
+fn part_1(report: &Report) {
+    let gamma = column_rating(report, BitCriteria::GAMMA).unwrap();
+    let epsilon = column_rating(report, BitCriteria::EPSILON).unwrap();
+    println!("part 1: {}", product(gamma, epsilon));
+}
+
+fn part_2(report: &Report) {
+    let index = PrefixIndex::new(report);
+    let oxygen_rating = index.rating(BitCriteria::OXYGEN).unwrap();
+    let co2_rating = index.rating(BitCriteria::CO2).unwrap();
+    println!("part 2:  {}", product(oxygen_rating, co2_rating));
+}
+
+// rows up to 128 bits wide can give ratings whose product doesn't fit a u128,
+// in which case the factors are shown instead
+fn product(a: u128, b: u128) -> String {
+    match a.checked_mul(b) {
+        Some(n) => n.to_string(),
+        None => format!("{} * {} (too large for a u128)", a, b),
+    }
 }
@@ -0,0 +1,160 @@
+// Each row is stored as a single integer with column 0 in the most
+// significant of `width` bits, so rows up to 128 columns wide fit in a u128.
+
+// A row may carry a weight after its bits (`10110 3`); rows without one
+// weigh 1, and `weights` stays `None` when no row has one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub rows: Vec<u128>,
+    pub width: usize,
+    pub weights: Option<Vec<u64>>,
+}
+
+impl Report {
+    pub fn parse(input: &str) -> Report {
+        let mut width = 0;
+        let mut weighted = false;
+        let (rows, weights): (Vec<_>, Vec<_>) = input
+            .split("\n")
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut parts = line.split_whitespace();
+                let bits = parts.next().unwrap();
+                if width == 0 {
+                    width = bits.len();
+                    if width > 128 {
+                        panic!("rows wider than 128 bits are not supported");
+                    }
+                } else if bits.len() != width {
+                    panic!("ragged row");
+                }
+                let weight = match parts.next() {
+                    Some(w) => {
+                        weighted = true;
+                        w.parse::<u64>().expect("invalid weight")
+                    }
+                    None => 1,
+                };
+                let row = bits.bytes().fold(0u128, |acc, ch| match ch {
+                    b'0' => acc << 1,
+                    b'1' => (acc << 1) | 1,
+                    _ => {
+                        panic!("invalid char")
+                    }
+                });
+                (row, weight)
+            })
+            .unzip();
+        Report {
+            rows,
+            width,
+            weights: if weighted { Some(weights) } else { None },
+        }
+    }
+
+    pub fn weight(&self, i: usize) -> u64 {
+        self.weights.as_ref().map_or(1, |w| w[i])
+    }
+
+    pub fn total_weight(&self) -> u64 {
+        match &self.weights {
+            Some(weights) => weights.iter().sum(),
+            None => self.rows.len() as u64,
+        }
+    }
+
+    // weight of the rows with a 1 in each column
+    pub fn column_weights(&self) -> Vec<u64> {
+        match &self.weights {
+            None => self
+                .column_counts()
+                .into_iter()
+                .map(|count| count as u64)
+                .collect(),
+            Some(weights) => (0..self.width)
+                .map(|col| {
+                    let mask = self.mask(col);
+                    self.rows
+                        .iter()
+                        .zip(weights)
+                        .filter(|(row, _)| *row & mask != 0)
+                        .map(|(_, w)| w)
+                        .sum()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn mask(&self, col: usize) -> u128 {
+        1 << (self.width - 1 - col)
+    }
+
+    // Bit-sliced counting: `planes[k]` holds bit k of every column's count, so
+    // adding a row is a ripple-carry add across all columns at once and costs
+    // about two word operations per row on average.
+    pub fn column_counts(&self) -> Vec<usize> {
+        count_columns(&self.rows, self.width)
+    }
+}
+
+pub fn count_columns(rows: &[u128], width: usize) -> Vec<usize> {
+    let mut planes: Vec<u128> = Vec::new();
+    for &row in rows {
+        let mut carry = row;
+        for plane in planes.iter_mut() {
+            if carry == 0 {
+                break;
+            }
+            let next = *plane & carry;
+            *plane ^= carry;
+            carry = next;
+        }
+        if carry != 0 {
+            planes.push(carry);
+        }
+    }
+
+    (0..width)
+        .map(|col| {
+            let bit = width - 1 - col;
+            planes
+                .iter()
+                .enumerate()
+                .map(|(k, plane)| (((plane >> bit) & 1) as usize) << k)
+                .sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counts_match_naive() {
+        let report = Report::parse(include_str!("test-input.txt"));
+        let naive = (0..report.width)
+            .map(|col| {
+                let mask = report.mask(col);
+                report.rows.iter().filter(|row| *row & mask != 0).count()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(report.column_counts(), naive);
+        assert_eq!(report.column_counts(), vec![7, 5, 8, 7, 5]);
+    }
+
+    #[test]
+    fn wide_rows() {
+        let row = "1".repeat(128);
+        let report = Report::parse(&format!("{}\n{}\n", row, row));
+        assert_eq!(report.column_counts(), vec![2; 128]);
+    }
+
+    #[test]
+    fn weighted_rows() {
+        let report = Report::parse("101 3\n010\n011 2\n");
+        assert_eq!(report.weights, Some(vec![3, 1, 2]));
+        assert_eq!(report.total_weight(), 6);
+        assert_eq!(report.column_weights(), vec![3, 3, 5]);
+    }
+}
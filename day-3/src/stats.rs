@@ -0,0 +1,123 @@
+use crate::criteria::{column_rating, BitCriteria, Tie};
+use crate::index::PrefixIndex;
+use crate::packed::Report;
+
+// rows listed per filtering step before the text report just counts them
+const MAX_LISTED: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub col: usize,
+    pub ones: u64,
+    pub proportion: f64,
+    // in bits, so 1.0 for a perfectly balanced column
+    pub entropy: f64,
+    pub tied: bool,
+}
+
+pub fn column_stats(report: &Report) -> Vec<ColumnStats> {
+    let total = report.total_weight();
+    report
+        .column_weights()
+        .into_iter()
+        .enumerate()
+        .map(|(col, ones)| {
+            let p = if total == 0 {
+                0.0
+            } else {
+                ones as f64 / total as f64
+            };
+            let entropy = [p, 1.0 - p]
+                .iter()
+                .filter(|p| **p > 0.0)
+                .map(|p| -p * p.log2())
+                .sum();
+            ColumnStats {
+                col,
+                ones,
+                proportion: p,
+                entropy,
+                tied: ones * 2 == total,
+            }
+        })
+        .collect()
+}
+
+pub fn print_report(report: &Report) {
+    let width = report.width;
+    let bits = |value: Result<u128, Tie>| match value {
+        Ok(value) => format!("{:0w$b} ({})", value, value, w = width),
+        Err(tie) => tie.to_string(),
+    };
+
+    println!(
+        "rows: {} (total weight {})",
+        report.rows.len(),
+        report.total_weight()
+    );
+    println!("col  ones  proportion  entropy  tied");
+    for stats in column_stats(report) {
+        let line = format!(
+            "{:>3}  {:>4}  {:>10.4}  {:>7.4}  {}",
+            stats.col,
+            stats.ones,
+            stats.proportion,
+            stats.entropy,
+            if stats.tied { "yes" } else { "" }
+        );
+        println!("{}", line.trim_end());
+    }
+    println!("gamma: {}", bits(column_rating(report, BitCriteria::GAMMA)));
+    println!(
+        "epsilon: {}",
+        bits(column_rating(report, BitCriteria::EPSILON))
+    );
+
+    let index = PrefixIndex::new(report);
+    for (label, criteria) in [("oxygen", BitCriteria::OXYGEN), ("co2", BitCriteria::CO2)] {
+        let (steps, rating) = index.filter_steps(criteria);
+        println!("{}: {}", label, bits(rating));
+        for step in steps {
+            let rows = index.rows(&step.kept);
+            let listed = rows
+                .iter()
+                .take(MAX_LISTED)
+                .map(|row| format!("{:0w$b}", row, w = width))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let more = if rows.len() > MAX_LISTED {
+                format!(" +{} more", rows.len() - MAX_LISTED)
+            } else {
+                String::new()
+            };
+            println!(
+                "  col {:>3} ({} ones / {} zeroes): {} left: {}{}",
+                step.col,
+                step.ones,
+                step.zeroes,
+                rows.len(),
+                listed,
+                more
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entropy_and_ties() {
+        let stats = column_stats(&Report::parse("10\n11\n00\n01"));
+        assert_eq!(stats[0].ones, 2);
+        assert!(stats[0].tied);
+        assert!((stats[0].entropy - 1.0).abs() < 1e-9);
+
+        let stats = column_stats(&Report::parse("10\n10\n10\n00"));
+        assert!(!stats[0].tied);
+        assert!((stats[0].proportion - 0.75).abs() < 1e-9);
+        assert!((stats[0].entropy - 0.811278).abs() < 1e-6);
+        assert_eq!(stats[1].entropy, 0.0);
+    }
+}
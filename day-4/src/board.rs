@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BingoBoard {
+    pub cells: HashMap<usize, (usize, usize)>,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl BingoBoard {
+    // one row per line; the board is as wide as its widest row
+    pub fn parse(text: &str) -> BingoBoard {
+        let mut cells = HashMap::new();
+        let mut rows = 0;
+        let mut cols = 0;
+        for line in text.split("\n").filter(|line| !line.trim().is_empty()) {
+            let mut width = 0;
+            for (col, cell) in line.split_whitespace().enumerate() {
+                let value = cell.parse::<usize>().unwrap();
+                cells.insert(value, (rows, col));
+                width = col + 1;
+            }
+            cols = cols.max(width);
+            rows += 1;
+        }
+        BingoBoard { cells, rows, cols }
+    }
+}
+
+// The draws line, then each board's text with the 1-based line it starts
+// on. Any run of lines with nothing but whitespace on them ends a board.
+pub fn split_input(input: &str) -> (&str, Vec<(usize, &str)>) {
+    let mut lines = input.split("\n").enumerate();
+    let draws = lines.next().map_or("", |(_, line)| line);
+    let mut boards = Vec::new();
+    // the current board's first line, and its byte range so far
+    let mut board: Option<(usize, usize, usize)> = None;
+    let mut offset = draws.len() + 1;
+    for (i, line) in lines {
+        let end = offset + line.len();
+        if line.trim().is_empty() {
+            boards.extend(board.take().map(|(at, from, to)| (at, &input[from..to])));
+        } else {
+            let (at, from, _) = board.unwrap_or((i + 1, offset, end));
+            board = Some((at, from, end));
+        }
+        offset = end + 1;
+    }
+    boards.extend(board.map(|(at, from, to)| (at, &input[from..to])));
+    (draws, boards)
+}
+
+// the draws and boards from split_input; call validate first, as this
+// panics on anything that isn't a number
+pub fn parse_input(input: &str) -> (Vec<usize>, Vec<BingoBoard>) {
+    let (draws, boards) = split_input(input);
+    let seq = draws
+        .split(",")
+        .map(|x| x.parse::<usize>().unwrap())
+        .collect::<Vec<_>>();
+    let boards = boards
+        .iter()
+        .map(|(_, text)| BingoBoard::parse(text))
+        .collect::<Vec<_>>();
+    (seq, boards)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternKind {
+    Rows,
+    Columns,
+    Diagonals,
+    Corners,
+    FullCard,
+}
+
+impl PatternKind {
+    pub fn parse(s: &str) -> Option<PatternKind> {
+        match s {
+            "rows" => Some(PatternKind::Rows),
+            "columns" => Some(PatternKind::Columns),
+            "diagonals" => Some(PatternKind::Diagonals),
+            "corners" => Some(PatternKind::Corners),
+            "full" => Some(PatternKind::FullCard),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WinPattern {
+    Row(usize),
+    Column(usize),
+    Diagonal,
+    AntiDiagonal,
+    FourCorners,
+    FullCard,
+}
+
+impl fmt::Display for WinPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WinPattern::Row(row) => write!(f, "row {}", row),
+            WinPattern::Column(col) => write!(f, "column {}", col),
+            WinPattern::Diagonal => write!(f, "diagonal"),
+            WinPattern::AntiDiagonal => write!(f, "anti-diagonal"),
+            WinPattern::FourCorners => write!(f, "four corners"),
+            WinPattern::FullCard => write!(f, "full card"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WinRules {
+    pub kinds: Vec<PatternKind>,
+}
+
+impl Default for WinRules {
+    fn default() -> WinRules {
+        WinRules {
+            kinds: vec![PatternKind::Rows, PatternKind::Columns],
+        }
+    }
+}
+
+impl WinRules {
+    // comma separated, e.g. `rows,columns,diagonals`
+    pub fn parse(s: &str) -> Option<WinRules> {
+        let kinds = s
+            .split(",")
+            .map(PatternKind::parse)
+            .collect::<Option<Vec<_>>>()?;
+        Some(WinRules { kinds })
+    }
+
+    // Every concrete pattern on a board of this size, each with the cells
+    // it needs. Diagonals only exist on square boards.
+    pub fn patterns(&self, rows: usize, cols: usize) -> Vec<(WinPattern, Vec<(usize, usize)>)> {
+        let mut out = Vec::new();
+        for kind in &self.kinds {
+            match kind {
+                PatternKind::Rows => {
+                    for row in 0..rows {
+                        let cells = (0..cols).map(|col| (row, col)).collect();
+                        out.push((WinPattern::Row(row), cells));
+                    }
+                }
+                PatternKind::Columns => {
+                    for col in 0..cols {
+                        let cells = (0..rows).map(|row| (row, col)).collect();
+                        out.push((WinPattern::Column(col), cells));
+                    }
+                }
+                PatternKind::Diagonals if rows == cols => {
+                    let cells = (0..rows).map(|i| (i, i)).collect();
+                    out.push((WinPattern::Diagonal, cells));
+                    let cells = (0..rows).map(|i| (i, cols - 1 - i)).collect();
+                    out.push((WinPattern::AntiDiagonal, cells));
+                }
+                PatternKind::Diagonals => {}
+                PatternKind::Corners if rows > 0 && cols > 0 => {
+                    let mut cells =
+                        vec![(0, 0), (0, cols - 1), (rows - 1, 0), (rows - 1, cols - 1)];
+                    cells.sort_unstable();
+                    cells.dedup();
+                    out.push((WinPattern::FourCorners, cells));
+                }
+                PatternKind::Corners => {}
+                PatternKind::FullCard => {
+                    let cells = (0..rows)
+                        .flat_map(|row| (0..cols).map(move |col| (row, col)))
+                        .collect();
+                    out.push((WinPattern::FullCard, cells));
+                }
+            }
+        }
+        out
+    }
+}
+
+pub struct BingoResult {
+    pub score: usize,
+    pub win_turn: usize,
+    pub pattern: WinPattern,
+}
+
+// Marks one board as numbers are drawn. Patterns count down the cells they
+// still need, and a draw that takes any to zero completes the earliest such
+// pattern in rule order.
+pub struct Tracker<'a> {
+    pub board: &'a BingoBoard,
+    patterns: Vec<(WinPattern, Vec<(usize, usize)>)>,
+    needed: Vec<usize>,
+    by_cell: HashMap<(usize, usize), Vec<usize>>,
+    remaining: HashSet<usize>,
+}
+
+impl<'a> Tracker<'a> {
+    pub fn new(board: &'a BingoBoard, rules: &WinRules) -> Tracker<'a> {
+        let patterns = rules.patterns(board.rows, board.cols);
+        let needed = patterns.iter().map(|(_, cells)| cells.len()).collect();
+        let mut by_cell: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (i, (_, cells)) in patterns.iter().enumerate() {
+            for cell in cells {
+                by_cell.entry(*cell).or_default().push(i);
+            }
+        }
+        Tracker {
+            board,
+            patterns,
+            needed,
+            by_cell,
+            remaining: board.cells.keys().cloned().collect(),
+        }
+    }
+
+    // None if the number isn't on the board or was already marked
+    pub fn mark(&mut self, val: usize) -> Option<Mark> {
+        let pos = *self.board.cells.get(&val)?;
+        if !self.remaining.remove(&val) {
+            return None;
+        }
+        let mut completed = None;
+        for &i in self.by_cell.get(&pos).into_iter().flatten() {
+            self.needed[i] -= 1;
+            if self.needed[i] == 0 {
+                completed = Some(completed.map_or(i, |c: usize| c.min(i)));
+            }
+        }
+        Some(Mark {
+            pos,
+            completed: completed.map(|i| self.patterns[i].0),
+        })
+    }
+
+    pub fn is_marked(&self, val: usize) -> bool {
+        self.board.cells.contains_key(&val) && !self.remaining.contains(&val)
+    }
+
+    pub fn unmarked_sum(&self) -> usize {
+        self.remaining.iter().sum()
+    }
+}
+
+pub struct Mark {
+    pub pos: (usize, usize),
+    pub completed: Option<WinPattern>,
+}
+
+pub fn score_board(seq: &[usize], board: &BingoBoard, rules: &WinRules) -> Option<BingoResult> {
+    let mut tracker = Tracker::new(board, rules);
+
+    for (turn, val) in seq.iter().enumerate() {
+        if let Some(pattern) = tracker.mark(*val).and_then(|mark| mark.completed) {
+            return Some(BingoResult {
+                score: tracker.unmarked_sum() * val,
+                win_turn: turn,
+                pattern,
+            });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rectangular_boards() {
+        let board = BingoBoard::parse("1 2 3 4\n5 6 7 8\n9 10 11 12");
+        assert_eq!((board.rows, board.cols), (3, 4));
+
+        let result = score_board(&[2, 6, 10], &board, &WinRules::default()).unwrap();
+        assert_eq!(result.pattern, WinPattern::Column(1));
+        assert_eq!(result.win_turn, 2);
+        assert_eq!(result.score, (78 - 18) * 10);
+
+        // no diagonals on a non-square board
+        let rules = WinRules::parse("diagonals").unwrap();
+        assert!(score_board(&[1, 6, 11, 12], &board, &rules).is_none());
+
+        let rules = WinRules::parse("rows,corners").unwrap();
+        let result = score_board(&[1, 4, 9, 12], &board, &rules).unwrap();
+        assert_eq!(result.pattern, WinPattern::FourCorners);
+
+        let rules = WinRules::parse("full").unwrap();
+        let seq = (1..=12).rev().collect::<Vec<_>>();
+        let result = score_board(&seq, &board, &rules).unwrap();
+        assert_eq!(
+            (result.pattern, result.win_turn, result.score),
+            (WinPattern::FullCard, 11, 0)
+        );
+    }
+
+    #[test]
+    fn diagonals() {
+        let board = BingoBoard::parse("1 2 3\n4 5 6\n7 8 9");
+        let rules = WinRules::parse("rows,diagonals").unwrap();
+        let result = score_board(&[3, 5, 7], &board, &rules).unwrap();
+        assert_eq!(result.pattern, WinPattern::AntiDiagonal);
+    }
+}
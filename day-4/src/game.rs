@@ -0,0 +1,221 @@
+use crate::board::{BingoBoard, Tracker, WinPattern, WinRules};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Drawn {
+        turn: usize,
+        number: usize,
+    },
+    Marked {
+        board: usize,
+        turn: usize,
+        number: usize,
+        pos: (usize, usize),
+    },
+    Won {
+        board: usize,
+        turn: usize,
+        pattern: WinPattern,
+        score: usize,
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Drawn { turn, number } => write!(f, "turn {}: draw {}", turn, number),
+            Event::Marked {
+                board,
+                turn,
+                number,
+                pos,
+            } => write!(
+                f,
+                "board {} marks {} at row {} column {} on turn {}",
+                board, number, pos.0, pos.1, turn
+            ),
+            Event::Won {
+                board,
+                turn,
+                pattern,
+                score,
+            } => write!(
+                f,
+                "board {} wins on turn {} via {} (score {})",
+                board, turn, pattern, score
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Win {
+    pub board: usize,
+    pub turn: usize,
+    pub pattern: WinPattern,
+    pub score: usize,
+}
+
+// Steps every board through the draw sequence together. Boards keep being
+// marked after they win, but only their first win is recorded.
+pub struct Game<'a> {
+    seq: &'a [usize],
+    trackers: Vec<Tracker<'a>>,
+    wins: Vec<Win>,
+    turn: usize,
+}
+
+impl<'a> Game<'a> {
+    pub fn new(seq: &'a [usize], boards: &'a [BingoBoard], rules: &WinRules) -> Game<'a> {
+        Game {
+            seq,
+            trackers: boards
+                .iter()
+                .map(|board| Tracker::new(board, rules))
+                .collect(),
+            wins: Vec::new(),
+            turn: 0,
+        }
+    }
+
+    // number of draws made so far
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.turn >= self.seq.len()
+    }
+
+    // turns count from 1, so an event on turn n happens on the nth draw
+    pub fn step(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        let number = match self.seq.get(self.turn) {
+            Some(number) => *number,
+            None => return events,
+        };
+        self.turn += 1;
+        let turn = self.turn;
+        events.push(Event::Drawn { turn, number });
+
+        for (board, tracker) in self.trackers.iter_mut().enumerate() {
+            let mark = match tracker.mark(number) {
+                Some(mark) => mark,
+                None => continue,
+            };
+            events.push(Event::Marked {
+                board,
+                turn,
+                number,
+                pos: mark.pos,
+            });
+            let already_won = self.wins.iter().any(|win| win.board == board);
+            if let (Some(pattern), false) = (mark.completed, already_won) {
+                let win = Win {
+                    board,
+                    turn,
+                    pattern,
+                    score: tracker.unmarked_sum() * number,
+                };
+                self.wins.push(win);
+                events.push(Event::Won {
+                    board,
+                    turn,
+                    pattern,
+                    score: win.score,
+                });
+            }
+        }
+        events
+    }
+
+    pub fn run(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        while !self.is_finished() {
+            events.extend(self.step());
+        }
+        events
+    }
+
+    // advance until `turn` draws have been made
+    pub fn run_to(&mut self, turn: usize) {
+        while self.turn < turn && !self.is_finished() {
+            self.step();
+        }
+    }
+
+    // boards in the order they won so far; boards that never win are absent
+    pub fn wins(&self) -> &[Win] {
+        &self.wins
+    }
+
+    // the board as it stands now, with marked numbers in brackets
+    pub fn render(&self, board: usize) -> String {
+        let tracker = &self.trackers[board];
+        let board = tracker.board;
+        let mut grid = vec![vec![None; board.cols]; board.rows];
+        for (value, (row, col)) in board.cells.iter() {
+            grid[*row][*col] = Some(*value);
+        }
+        let width = board
+            .cells
+            .keys()
+            .map(|value| value.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        let mut out = String::new();
+        for row in grid {
+            let line = row
+                .iter()
+                .map(|cell| match cell {
+                    Some(value) if tracker.is_marked(*value) => {
+                        format!("[{:>w$}]", value, w = width)
+                    }
+                    Some(value) => format!(" {:>w$} ", value, w = width),
+                    None => format!(" {:>w$} ", ".", w = width),
+                })
+                .collect::<Vec<_>>()
+                .join("");
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::parse_input;
+
+    #[test]
+    fn win_order_and_render() {
+        let (seq, boards) = parse_input(include_str!("test-input.txt"));
+        let rules = WinRules::default();
+
+        let mut game = Game::new(&seq, &boards, &rules);
+        game.run_to(11);
+        assert!(game.wins().is_empty());
+        game.step();
+        assert_eq!(
+            game.wins(),
+            &[Win {
+                board: 2,
+                turn: 12,
+                pattern: WinPattern::Row(0),
+                score: 4512
+            }]
+        );
+        assert_eq!(game.turn(), 12);
+        assert!(game
+            .render(2)
+            .starts_with("[14][21][17][24][ 4]\n 10  16  15 [ 9] 19\n"));
+
+        game.run();
+        let order = game.wins().iter().map(|win| win.board).collect::<Vec<_>>();
+        assert_eq!(order, vec![2, 0, 1]);
+        assert_eq!(game.wins()[2].score, 1924);
+    }
+}
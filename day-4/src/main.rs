@@ -1,32 +1,167 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
+mod board;
+mod game;
+mod generate;
+mod montecarlo;
+mod rng;
+mod solver;
+mod validate;
+
+use board::{score_board, WinRules};
+use game::{Event, Game};
+use solver::Solution;
 
 fn main() {
     let input = include_str!("input.txt");
-    let mut sections = input.split("\n\n");
-    let seq = sections
-        .next()
-        .unwrap()
-        .split(",")
-        .map(|x| x.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    // `cargo run -- generate <boards> [rows] [cols] [max] [seed]` prints a new
+    // random puzzle input instead of solving this one
+    if args.first().map(|s| s.as_str()) == Some("generate") {
+        let arg = |i: usize, default: usize| {
+            args.get(i)
+                .map(|s| s.parse::<usize>().expect("expected a number"))
+                .unwrap_or(default)
+        };
+        let max = arg(4, 99);
+        println!(
+            "{}",
+            generate::generate(arg(1, 100), arg(2, 5), arg(3, 5), max, arg(5, 2021) as u64)
+        );
+        return;
+    }
+
+    // `cargo run -- validate` lists every problem with the input, including
+    // ones that don't stop it being solved
+    let issues = validate::validate(input);
+    if args.first().map(|s| s.as_str()) == Some("validate") {
+        for issue in &issues {
+            println!("{}", issue);
+        }
+        println!("{} issues", issues.len());
+        return;
+    }
+    if issues.iter().any(|issue| issue.is_error()) {
+        for issue in issues.iter().filter(|issue| issue.is_error()) {
+            eprintln!("{}", issue);
+        }
+        std::process::exit(1);
+    }
+
+    let (seq, boards) = board::parse_input(input);
 
-    let boards = sections
-        .map(|board| {
-            let mut map = HashMap::new();
-            for (row, col_str) in board.split("\n").enumerate() {
-                for (col, cell) in col_str.split_whitespace().enumerate() {
-                    let value = cell.parse::<usize>().unwrap();
-                    map.insert(value, (row, col));
+    // `--patterns rows,columns,diagonals,corners,full` picks what counts as a win
+    let mut rules = WinRules::default();
+    if let Some(i) = args.iter().position(|arg| arg == "--patterns") {
+        rules = args
+            .get(i + 1)
+            .and_then(|s| WinRules::parse(s))
+            .expect("expected a comma separated list of patterns");
+        args.drain(i..=i + 1);
+    }
+
+    match args.first().map(|s| s.as_str()) {
+        // `cargo run -- log [all]` prints wins turn by turn, or every event
+        Some("log") => {
+            let all = args.get(1).map(|s| s.as_str()) == Some("all");
+            let mut game = Game::new(&seq, &boards, &rules);
+            for event in game.run() {
+                if all || matches!(event, Event::Won { .. }) {
+                    println!("{}", event);
                 }
             }
-            return map;
-        })
-        .collect::<Vec<_>>();
+            return;
+        }
+        // `cargo run -- order` lists every winning board, first to last
+        Some("order") => {
+            let mut game = Game::new(&seq, &boards, &rules);
+            game.run();
+            for (place, win) in game.wins().iter().enumerate() {
+                println!(
+                    "{}: board {} on turn {} via {} (score {})",
+                    place + 1,
+                    win.board,
+                    win.turn,
+                    win.pattern,
+                    win.score
+                );
+            }
+            return;
+        }
+        // `cargo run -- show <board> <turn>` renders a board after `turn` draws,
+        // as it stands right after the events `log` shows for that turn
+        Some("show") => {
+            let index = |i: usize| args.get(i).and_then(|s| s.parse::<usize>().ok());
+            let board = index(1).expect("board number");
+            if board >= boards.len() {
+                panic!("there are only {} boards", boards.len());
+            }
+            let mut game = Game::new(&seq, &boards, &rules);
+            game.run_to(index(2).unwrap_or(seq.len()));
+            println!("board {} after {} draws", board, game.turn());
+            print!("{}", game.render(board));
+            return;
+        }
+        // `cargo run -- solve <board>` finds the shortest draw order that
+        // makes that board the only first winner
+        Some("solve") => {
+            let target = args
+                .get(1)
+                .and_then(|s| s.parse::<usize>().ok())
+                .expect("board number");
+            if target >= boards.len() {
+                panic!("there are only {} boards", boards.len());
+            }
+            match solver::solve(&seq, &boards, target, &rules) {
+                Solution::Found { pattern, draws } => {
+                    let verified = solver::verify(&draws, &boards, target, &rules);
+                    println!(
+                        "board {} wins first via {} in {} draws (verified: {})",
+                        target,
+                        pattern,
+                        draws.len(),
+                        verified
+                    );
+                    let draws = draws.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+                    println!("{}", draws.join(","));
+                }
+                Solution::Impossible(blocked) => {
+                    println!("board {} can never win first:", target);
+                    for (pattern, reason) in blocked {
+                        println!("  {}: {}", pattern, reason);
+                    }
+                }
+            }
+            return;
+        }
+        // `cargo run -- odds [trials] [seed]` estimates each board's chance of
+        // winning first or last over shuffled draw orders
+        Some("odds") => {
+            let arg = |i: usize, default: u64| {
+                args.get(i)
+                    .map(|s| s.parse::<u64>().expect("expected a number"))
+                    .unwrap_or(default)
+            };
+            let trials = arg(1, 10_000).max(1);
+            let seed = arg(2, 2021);
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            let tallies = montecarlo::simulate(&seq, &boards, &rules, trials, seed, threads);
+            println!("{} trials, seed {}", trials, seed);
+            for (board, tally) in tallies.iter().enumerate() {
+                let first = montecarlo::estimate(tally.first, trials);
+                let last = montecarlo::estimate(tally.last, trials);
+                println!(
+                    "board {}: first {:.4} [{:.4}, {:.4}]  last {:.4} [{:.4}, {:.4}]",
+                    board, first.p, first.low, first.high, last.p, last.low, last.high
+                );
+            }
+            return;
+        }
+        _ => {}
+    }
 
     let scored_boards = boards
         .iter()
-        .filter_map(|board| score_board(&seq, &board))
+        .filter_map(|board| score_board(&seq, board, &rules))
         .collect::<Vec<_>>();
 
     let first_winner = scored_boards
@@ -39,37 +174,6 @@ fn main() {
         .max_by(|l, r| l.win_turn.cmp(&r.win_turn))
         .unwrap();
 
-    println!("Part 1: {}", first_winner.score);
-    println!("Part 2: {}", last_winner.score);
-}
-
-struct BingoResult {
-    score: usize,
-    win_turn: usize,
-}
-
-type BingoBoard = HashMap<usize, (usize, usize)>;
-
-fn score_board(seq: &[usize], board: &BingoBoard) -> Option<BingoResult> {
-    let mut rows = vec![0; 5];
-    let mut cols = vec![0; 5];
-    let mut remaining = board.keys().cloned().collect::<HashSet<usize>>();
-
-    for (turn, val) in seq.iter().enumerate() {
-        if let Some((row, col)) = board.get(&val) {
-            remaining.remove(&val);
-            rows[*row] += 1;
-            cols[*col] += 1;
-            if rows[*row] == 5 || cols[*col] == 5 {
-                let sum = remaining.iter().sum::<usize>();
-
-                return Some(BingoResult {
-                    score: sum * val,
-                    win_turn: turn,
-                });
-            }
-        }
-    }
-
-    None
+    println!("Part 1: {} ({})", first_winner.score, first_winner.pattern);
+    println!("Part 2: {} ({})", last_winner.score, last_winner.pattern);
 }
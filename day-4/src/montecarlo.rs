@@ -0,0 +1,131 @@
+use crate::board::{score_board, BingoBoard, WinRules};
+use crate::rng::Rng;
+use std::thread;
+
+// Each trial shuffles the draw sequence with its own seed derived from the
+// base seed and the trial number, so results don't depend on how trials are
+// split between threads. Boards tied for the earliest (or latest) win turn
+// all count as first (or last), so probabilities can sum to more than 1.
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Tally {
+    pub first: u64,
+    pub last: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub p: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+// 95% Wilson score interval, which behaves at counts of 0 and n
+pub fn estimate(hits: u64, trials: u64) -> Estimate {
+    let z = 1.96;
+    let n = trials as f64;
+    let p = hits as f64 / n;
+    let denom = 1.0 + z * z / n;
+    let centre = (p + z * z / (2.0 * n)) / denom;
+    let spread = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denom;
+    Estimate {
+        p,
+        low: (centre - spread).max(0.0),
+        high: (centre + spread).min(1.0),
+    }
+}
+
+fn trial_seed(seed: u64, trial: u64) -> u64 {
+    Rng::new(seed ^ trial.wrapping_mul(0x2545_f491_4f6c_dd1d)).next_u64()
+}
+
+fn run_trials(
+    seq: &[usize],
+    boards: &[BingoBoard],
+    rules: &WinRules,
+    seed: u64,
+    trials: std::ops::Range<u64>,
+) -> Vec<Tally> {
+    let mut tallies = vec![Tally::default(); boards.len()];
+    let mut draws = seq.to_vec();
+    for trial in trials {
+        draws.copy_from_slice(seq);
+        Rng::new(trial_seed(seed, trial)).shuffle(&mut draws);
+        let turns = boards
+            .iter()
+            .map(|board| score_board(&draws, board, rules).map(|r| r.win_turn))
+            .collect::<Vec<_>>();
+        let first = turns.iter().flatten().min();
+        let last = turns.iter().flatten().max();
+        for (tally, turn) in tallies.iter_mut().zip(&turns) {
+            if turn.is_some() && turn.as_ref() == first {
+                tally.first += 1;
+            }
+            if turn.is_some() && turn.as_ref() == last {
+                tally.last += 1;
+            }
+        }
+    }
+    tallies
+}
+
+pub fn simulate(
+    seq: &[usize],
+    boards: &[BingoBoard],
+    rules: &WinRules,
+    trials: u64,
+    seed: u64,
+    threads: usize,
+) -> Vec<Tally> {
+    let threads = threads.max(1) as u64;
+    let chunk = trials.div_ceil(threads);
+    let results = thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|t| {
+                let range = (t * chunk).min(trials)..((t + 1) * chunk).min(trials);
+                scope.spawn(move || run_trials(seq, boards, rules, seed, range))
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let mut total = vec![Tally::default(); boards.len()];
+    for tallies in results {
+        for (sum, tally) in total.iter_mut().zip(tallies) {
+            sum.first += tally.first;
+            sum.last += tally.last;
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::parse_input;
+
+    #[test]
+    fn thread_count_does_not_change_results() {
+        let (seq, boards) = parse_input(include_str!("test-input.txt"));
+        let rules = WinRules::default();
+
+        let one = simulate(&seq, &boards, &rules, 200, 7, 1);
+        let four = simulate(&seq, &boards, &rules, 200, 7, 4);
+        assert_eq!(one, four);
+        assert!(one.iter().map(|t| t.first).sum::<u64>() >= 200);
+    }
+
+    #[test]
+    fn wilson_interval() {
+        let e = estimate(50, 100);
+        assert!((e.p - 0.5).abs() < 1e-9);
+        assert!((e.low - 0.4038).abs() < 1e-3);
+        assert!((e.high - 0.5962).abs() < 1e-3);
+        let e = estimate(0, 100);
+        assert_eq!(e.low, 0.0);
+        assert!(e.high > 0.0);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::board::{score_board, BingoBoard, WinPattern, WinRules};
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+
+// Finds the shortest draw order that makes one board the sole winner.
+//
+// The target has to complete some pattern P, so any winning draw set contains
+// the numbers of P. Drawing more numbers can only complete patterns on other
+// boards, never stop them, so if any draw set works then the numbers of P
+// alone work too, in any order. The answer is the smallest pattern whose
+// numbers are all in the draw pool and complete nothing on another board;
+// when there is none, each pattern's blocker is the proof.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Blocker {
+    NeverDrawn(usize),
+    Completes { board: usize, pattern: WinPattern },
+}
+
+impl fmt::Display for Blocker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Blocker::NeverDrawn(number) => write!(f, "{} is never drawn", number),
+            Blocker::Completes { board, pattern } => {
+                write!(f, "it also completes board {} {}", board, pattern)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solution {
+    Found {
+        pattern: WinPattern,
+        draws: Vec<usize>,
+    },
+    Impossible(Vec<(WinPattern, Blocker)>),
+}
+
+fn pattern_values(board: &BingoBoard, cells: &[(usize, usize)]) -> Option<Vec<usize>> {
+    let by_pos = board
+        .cells
+        .iter()
+        .map(|(value, pos)| (*pos, *value))
+        .collect::<HashMap<_, _>>();
+    cells.iter().map(|pos| by_pos.get(pos).cloned()).collect()
+}
+
+fn blocker(
+    draws: &HashSet<usize>,
+    pool: &HashSet<usize>,
+    boards: &[BingoBoard],
+    target: usize,
+    rules: &WinRules,
+) -> Option<Blocker> {
+    let mut missing = draws
+        .iter()
+        .filter(|n| !pool.contains(n))
+        .collect::<Vec<_>>();
+    missing.sort_unstable();
+    if let Some(number) = missing.first() {
+        return Some(Blocker::NeverDrawn(**number));
+    }
+    for (i, board) in boards.iter().enumerate() {
+        if i == target {
+            continue;
+        }
+        for (pattern, cells) in rules.patterns(board.rows, board.cols) {
+            let complete = pattern_values(board, &cells)
+                .is_some_and(|values| values.iter().all(|v| draws.contains(v)));
+            if complete {
+                return Some(Blocker::Completes { board: i, pattern });
+            }
+        }
+    }
+    None
+}
+
+// `seq` is the pool of numbers that may be drawn, and the order in which the
+// chosen ones are returned
+pub fn solve(seq: &[usize], boards: &[BingoBoard], target: usize, rules: &WinRules) -> Solution {
+    let pool = seq.iter().cloned().collect::<HashSet<_>>();
+    let board = &boards[target];
+    let mut patterns = rules
+        .patterns(board.rows, board.cols)
+        .into_iter()
+        .filter_map(|(pattern, cells)| Some((pattern, pattern_values(board, &cells)?)))
+        .collect::<Vec<_>>();
+    patterns.sort_by_key(|(_, values)| values.len());
+
+    let mut blocked = Vec::new();
+    for (pattern, values) in patterns {
+        let draws = values.iter().cloned().collect::<HashSet<_>>();
+        match blocker(&draws, &pool, boards, target, rules) {
+            Some(reason) => blocked.push((pattern, reason)),
+            None => {
+                // the pool may repeat a number; only its first draw counts
+                let mut draws = draws;
+                let draws = seq
+                    .iter()
+                    .filter(|n| draws.remove(n))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                return Solution::Found { pattern, draws };
+            }
+        }
+    }
+    Solution::Impossible(blocked)
+}
+
+// replays a found order through `score_board` for every board
+pub fn verify(draws: &[usize], boards: &[BingoBoard], target: usize, rules: &WinRules) -> bool {
+    boards.iter().enumerate().all(|(i, board)| {
+        let result = score_board(draws, board, rules);
+        if i == target {
+            result.is_some_and(|r| r.win_turn + 1 == draws.len())
+        } else {
+            result.is_none()
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn solves_and_proves() {
+        let boards = vec![
+            BingoBoard::parse("1 2\n3 4"),
+            BingoBoard::parse("1 2\n5 6"),
+            BingoBoard::parse("7 1\n8 2"),
+        ];
+        let rules = WinRules::default();
+        let seq = (0..10).collect::<Vec<_>>();
+
+        // board 0's top row is also the top row of board 1, so the solver
+        // must pick another line
+        match solve(&seq, &boards, 0, &rules) {
+            Solution::Found { pattern, draws } => {
+                assert_eq!(pattern, WinPattern::Row(1));
+                assert_eq!(draws, vec![3, 4]);
+                assert!(verify(&draws, &boards, 0, &rules));
+            }
+            other => panic!("{:?}", other),
+        }
+
+        // a repeated draw is only used once
+        let seq = vec![3, 3, 4, 9];
+        match solve(&seq, &boards, 0, &rules) {
+            Solution::Found { draws, .. } => assert_eq!(draws, vec![3, 4]),
+            other => panic!("{:?}", other),
+        }
+
+        // board 1's top row completes board 0's, and every other line needs
+        // a number that's never drawn
+        let seq = vec![1, 2, 3, 4, 7, 8];
+        assert_eq!(
+            solve(&seq, &boards, 1, &rules),
+            Solution::Impossible(vec![
+                (
+                    WinPattern::Row(0),
+                    Blocker::Completes {
+                        board: 0,
+                        pattern: WinPattern::Row(0)
+                    }
+                ),
+                (WinPattern::Row(1), Blocker::NeverDrawn(5)),
+                (WinPattern::Column(0), Blocker::NeverDrawn(5)),
+                (WinPattern::Column(1), Blocker::NeverDrawn(6)),
+            ])
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::board::split_input;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+
+// Problems found in a raw puzzle input. Line numbers are 1-based and boards
+// are numbered from 0 in the order they appear, as everywhere else.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    BadDraw {
+        index: usize,
+        text: String,
+    },
+    DuplicateDraw {
+        index: usize,
+        value: usize,
+    },
+    BadCell {
+        line: usize,
+        text: String,
+    },
+    DuplicateNumber {
+        board: usize,
+        line: usize,
+        value: usize,
+        first_line: usize,
+    },
+    RaggedRow {
+        board: usize,
+        line: usize,
+        len: usize,
+        expected: usize,
+    },
+    UnusedDraw {
+        value: usize,
+    },
+}
+
+impl Issue {
+    // Unused draws are harmless, and so are repeated ones, as a number that's
+    // already marked stays marked; everything else changes the answer.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Issue::UnusedDraw { .. } | Issue::DuplicateDraw { .. })
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::BadDraw { index, text } => {
+                write!(f, "line 1: draw {} is not a number: {:?}", index, text)
+            }
+            Issue::DuplicateDraw { index, value } => {
+                write!(f, "line 1: draw {} repeats {}", index, value)
+            }
+            Issue::BadCell { line, text } => {
+                write!(f, "line {}: {:?} is not a number", line, text)
+            }
+            Issue::DuplicateNumber {
+                board,
+                line,
+                value,
+                first_line,
+            } => write!(
+                f,
+                "line {}: board {} already has {} on line {}",
+                line, board, value, first_line
+            ),
+            Issue::RaggedRow {
+                board,
+                line,
+                len,
+                expected,
+            } => write!(
+                f,
+                "line {}: board {} row has {} numbers, expected {}",
+                line, board, len, expected
+            ),
+            Issue::UnusedDraw { value } => write!(f, "draw {} is on no board", value),
+        }
+    }
+}
+
+pub fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let (first, boards) = split_input(input);
+
+    let mut draws = Vec::new();
+    let mut seen_draws = HashSet::new();
+    for (index, text) in first.split(",").enumerate() {
+        match text.trim().parse::<usize>() {
+            Ok(value) => {
+                if !seen_draws.insert(value) {
+                    issues.push(Issue::DuplicateDraw { index, value });
+                }
+                draws.push(value);
+            }
+            Err(_) => issues.push(Issue::BadDraw {
+                index,
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    let mut on_boards = HashSet::new();
+    for (board, (first_line, text)) in boards.iter().enumerate() {
+        let mut width = None;
+        let mut numbers: HashMap<usize, usize> = HashMap::new();
+        for (line, text) in (*first_line..).zip(text.split("\n")) {
+            let cells = text.split_whitespace().collect::<Vec<_>>();
+            match width {
+                None => width = Some(cells.len()),
+                Some(expected) if cells.len() != expected => issues.push(Issue::RaggedRow {
+                    board,
+                    line,
+                    len: cells.len(),
+                    expected,
+                }),
+                Some(_) => {}
+            }
+            for cell in cells {
+                let value = match cell.parse::<usize>() {
+                    Ok(value) => value,
+                    Err(_) => {
+                        issues.push(Issue::BadCell {
+                            line,
+                            text: cell.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                on_boards.insert(value);
+                // keep the line of the first copy, however many follow
+                match numbers.entry(value) {
+                    Entry::Occupied(first) => issues.push(Issue::DuplicateNumber {
+                        board,
+                        line,
+                        value,
+                        first_line: *first.get(),
+                    }),
+                    Entry::Vacant(slot) => {
+                        slot.insert(line);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut reported = HashSet::new();
+    for value in draws {
+        if !on_boards.contains(&value) && reported.insert(value) {
+            issues.push(Issue::UnusedDraw { value });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clean_input() {
+        assert_eq!(validate(include_str!("test-input.txt")), vec![]);
+    }
+
+    #[test]
+    fn finds_issues() {
+        let input = "1,2,x,9,2\n\n1 2\n3 1\n\n\n4 5\n6\n";
+        assert_eq!(
+            validate(input),
+            vec![
+                Issue::BadDraw {
+                    index: 2,
+                    text: "x".to_string()
+                },
+                Issue::DuplicateDraw { index: 4, value: 2 },
+                Issue::DuplicateNumber {
+                    board: 0,
+                    line: 4,
+                    value: 1,
+                    first_line: 3
+                },
+                Issue::RaggedRow {
+                    board: 1,
+                    line: 8,
+                    len: 1,
+                    expected: 2
+                },
+                Issue::UnusedDraw { value: 9 },
+            ]
+        );
+        assert!(!Issue::DuplicateDraw { index: 4, value: 2 }.is_error());
+
+        // every later copy points back at the first
+        let lines = validate("1\n\n1 2\n3 1\n1 4\n5 5")
+            .into_iter()
+            .filter_map(|issue| match issue {
+                Issue::DuplicateNumber {
+                    line, first_line, ..
+                } => Some((line, first_line)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![(4, 3), (5, 3), (6, 6)]);
+    }
+
+    #[test]
+    fn splits_boards_like_the_parser() {
+        // a line of spaces ends a board here just as it does in parse_input
+        let input = "1,2,5,6\n\n1 2\n3 4\n  \n5 6\n7 8";
+        assert_eq!(validate(input), vec![]);
+        assert_eq!(split_input(input).1, vec![(3, "1 2\n3 4"), (6, "5 6\n7 8")]);
+        let (_, boards) = crate::board::parse_input(input);
+        assert_eq!(boards.len(), 2);
+        assert_eq!((boards[1].rows, boards[1].cols), (2, 2));
+    }
+}
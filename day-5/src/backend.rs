@@ -0,0 +1,392 @@
+use crate::lines::{self, Lines};
+use crate::segment::Segment;
+use crate::Coord;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+// bounding boxes up to this many cells get a dense grid (32MB of u16s)
+pub const DENSE_MAX_CELLS: u64 = 1 << 24;
+
+pub trait Backend {
+    fn name(&self) -> &'static str;
+    // number of points covered by at least two segments
+    fn count_overlaps(&self, segments: &[Segment]) -> usize;
+    // false when this backend can't count these segments as it is meant to
+    fn fits(&self, _segments: &[Segment]) -> bool {
+        true
+    }
+}
+
+pub fn by_name(name: &str) -> Option<Box<dyn Backend>> {
+    match name {
+        "hash" => Some(Box::new(HashBackend)),
+        "dense" => Some(Box::new(DenseGrid)),
+        "sweep" => Some(Box::new(SweepLine)),
+        _ => None,
+    }
+}
+
+// dense when the bounding box is small enough, otherwise sweep
+pub fn choose(segments: &[Segment]) -> Box<dyn Backend> {
+    if fits_dense(segments) {
+        Box::new(DenseGrid)
+    } else {
+        Box::new(SweepLine)
+    }
+}
+
+pub fn fits_dense(segments: &[Segment]) -> bool {
+    bounds(segments).is_none_or(|(min, max)| {
+        let area = ((max.0 - min.0 + 1) as u64).checked_mul((max.1 - min.1 + 1) as u64);
+        area.is_some_and(|area| area <= DENSE_MAX_CELLS)
+    })
+}
+
+pub fn bounds(segments: &[Segment]) -> Option<(Coord, Coord)> {
+    let mut points = segments.iter().flat_map(|s| [s.from, s.to]);
+    let first = points.next()?;
+    Some(points.fold((first, first), |(min, max), p| {
+        (
+            (min.0.min(p.0), min.1.min(p.1)),
+            (max.0.max(p.0), max.1.max(p.1)),
+        )
+    }))
+}
+
+// one map entry per covered point
+pub struct HashBackend;
+
+impl HashBackend {
+    pub fn fill(segments: &[Segment]) -> HashMap<Coord, usize> {
+        let mut map: HashMap<Coord, usize> = HashMap::new();
+        for segment in segments {
+            for point in segment.points() {
+                map.entry(point)
+                    .and_modify(|count| *count += 1)
+                    .or_insert(1);
+            }
+        }
+        map
+    }
+}
+
+impl Backend for HashBackend {
+    fn name(&self) -> &'static str {
+        "hash"
+    }
+    fn count_overlaps(&self, segments: &[Segment]) -> usize {
+        let map = HashBackend::fill(segments);
+        map.values().filter(|val| **val > 1).count()
+    }
+}
+
+// one saturating u16 per cell of the bounding box
+pub struct DenseGrid;
+
+pub struct Grid {
+    pub min: Coord,
+    pub max: Coord,
+    pub width: usize,
+    pub height: usize,
+    pub counts: Vec<u16>,
+}
+
+impl Grid {
+    pub fn get(&self, (x, y): Coord) -> u16 {
+        if x < self.min.0 || y < self.min.1 || x > self.max.0 || y > self.max.1 {
+            return 0;
+        }
+        self.counts[(y - self.min.1) as usize * self.width + (x - self.min.0) as usize]
+    }
+}
+
+impl DenseGrid {
+    // None when there are no segments or their bounding box is too large
+    pub fn fill(segments: &[Segment]) -> Option<Grid> {
+        if !fits_dense(segments) {
+            return None;
+        }
+        let (min, max) = bounds(segments)?;
+        let width = (max.0 - min.0 + 1) as usize;
+        let height = (max.1 - min.1 + 1) as usize;
+        let mut counts = vec![0u16; width * height];
+        for segment in segments {
+            for (x, y) in segment.points() {
+                let cell = &mut counts[(y - min.1) as usize * width + (x - min.0) as usize];
+                *cell = cell.saturating_add(1);
+            }
+        }
+        Some(Grid {
+            min,
+            max,
+            width,
+            height,
+            counts,
+        })
+    }
+}
+
+impl Backend for DenseGrid {
+    fn name(&self) -> &'static str {
+        "dense"
+    }
+    // too large a bounding box falls back to the sweep rather than allocate it
+    fn count_overlaps(&self, segments: &[Segment]) -> usize {
+        match DenseGrid::fill(segments) {
+            Some(grid) => grid.counts.iter().filter(|val| **val > 1).count(),
+            None => SweepLine.count_overlaps(segments),
+        }
+    }
+    fn fits(&self, segments: &[Segment]) -> bool {
+        fits_dense(segments)
+    }
+}
+
+// Sweeps x from left to right. Vertical segments are y intervals in a single
+// column and horizontal ones are y values held across a run of columns, so
+// neither is walked point by point; columns with nothing but horizontal
+// segments in them are counted in bulk. Sloped segments are counted along
+// their own lines, plus a check of every sloped segment against every other
+// segment for the points where they cross.
+pub struct SweepLine;
+
+impl Backend for SweepLine {
+    fn name(&self) -> &'static str {
+        "sweep"
+    }
+    fn count_overlaps(&self, segments: &[Segment]) -> usize {
+        let (axis, sloped): (Vec<Segment>, Vec<Segment>) = segments
+            .iter()
+            .partition(|s| s.kind() == crate::SegmentKind::Axis);
+        let mut total = sweep_axis(&axis);
+        if sloped.is_empty() {
+            return total as usize;
+        }
+
+        let lines = Lines::build(segments);
+        total += lines
+            .keys()
+            .filter(|key| !lines::is_axis(key))
+            .map(|key| lines.dangerous(key))
+            .sum::<u64>();
+        // Every point where a sloped segment meets another line is dangerous.
+        // It's been counted once for each of its sloped lines with two
+        // segments over it, and once more if the axis segments alone cover it
+        // twice; make that exactly once.
+        for (at, keys) in lines::crossings(&sloped, segments) {
+            let axis_depth = keys
+                .iter()
+                .filter(|key| lines::is_axis(key))
+                .map(|key| lines.depth(key, at))
+                .sum::<u64>();
+            let counted = keys
+                .iter()
+                .filter(|key| !lines::is_axis(key) && lines.depth(key, at) > 1)
+                .count() as u64
+                + (axis_depth > 1) as u64;
+            total = total + 1 - counted;
+        }
+        total as usize
+    }
+}
+
+fn sweep_axis(segments: &[Segment]) -> u64 {
+    let mut verticals: BTreeMap<i64, Vec<(i64, i64)>> = BTreeMap::new();
+    let mut starts: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+    let mut ends: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+
+    for segment in segments {
+        let (mut a, mut b) = (segment.from, segment.to);
+        if a.0 > b.0 {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let ((x0, y0), (x1, y1)) = (a, b);
+        if x0 == x1 {
+            verticals
+                .entry(x0)
+                .or_default()
+                .push((y0.min(y1), y0.max(y1)));
+        } else {
+            starts.entry(x0).or_default().push(y0);
+            ends.entry(x1).or_default().push(y0);
+        }
+    }
+
+    // multiset of y values of the horizontal segments crossing the sweep
+    let mut active: BTreeMap<i64, u64> = BTreeMap::new();
+    let mut active_dups = 0u64;
+    let mut total = 0u64;
+    let mut prev: Option<i64> = None;
+
+    loop {
+        let next = [
+            verticals.keys().next().cloned(),
+            starts.keys().next().cloned(),
+            // a horizontal segment ending at x is still there at x
+            ends.keys().next().map(|x| x + 1),
+        ]
+        .iter()
+        .flatten()
+        .min()
+        .cloned();
+        let x = match next {
+            Some(x) => x,
+            None => break,
+        };
+
+        if let Some(prev) = prev {
+            total += (x - prev - 1) as u64 * active_dups;
+        }
+
+        if let Some(ys) = ends.range(..x).next().map(|(k, _)| *k) {
+            for y in ends.remove(&ys).unwrap() {
+                let count = active.get_mut(&y).unwrap();
+                if *count == 2 {
+                    active_dups -= 1;
+                }
+                *count -= 1;
+                if *count == 0 {
+                    active.remove(&y);
+                }
+            }
+        }
+        for y in starts.remove(&x).unwrap_or_default() {
+            let count = active.entry(y).or_insert(0);
+            *count += 1;
+            if *count == 2 {
+                active_dups += 1;
+            }
+        }
+
+        total += match verticals.remove(&x) {
+            Some(column) => column_overlaps(&active, column),
+            None => active_dups,
+        };
+        prev = Some(x);
+    }
+    total
+}
+
+// points covered at least twice in one column, given the horizontal
+// segments' y values and any other intervals in the column
+fn column_overlaps(active: &BTreeMap<i64, u64>, column: Vec<(i64, i64)>) -> u64 {
+    let mut events = Vec::with_capacity(2 * (active.len() + column.len()));
+    for (y, count) in active {
+        events.push((*y, *count as i64));
+        events.push((y + 1, -(*count as i64)));
+    }
+    for (lo, hi) in column {
+        events.push((lo, 1));
+        events.push((hi + 1, -1));
+    }
+    events.sort_unstable();
+
+    let mut covered = 0u64;
+    let mut depth = 0;
+    let mut from = 0;
+    for (y, delta) in events {
+        if depth > 1 {
+            covered += (y - from) as u64;
+        }
+        depth += delta;
+        from = y;
+    }
+    covered
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn backends() -> Vec<Box<dyn Backend>> {
+        ["hash", "dense", "sweep"]
+            .iter()
+            .map(|name| by_name(name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn example() {
+        let segments = crate::parse::parse_segments(include_str!("test-input.txt")).unwrap();
+        let axis = segments
+            .iter()
+            .filter(|s| s.kind() == crate::SegmentKind::Axis)
+            .cloned()
+            .collect::<Vec<_>>();
+        for backend in backends() {
+            assert_eq!(backend.count_overlaps(&axis), 5, "{}", backend.name());
+            assert_eq!(backend.count_overlaps(&segments), 12, "{}", backend.name());
+        }
+    }
+
+    #[test]
+    fn backends_agree() {
+        let mut state = 12345u64;
+        let mut next = |n: u64| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) % n) as i64 - 20
+        };
+        for _ in 0..50 {
+            let segments = (0..30)
+                .map(|_| Segment {
+                    from: (next(40), next(40)),
+                    to: (next(40), next(40)),
+                })
+                .collect::<Vec<_>>();
+            let expected = HashBackend.count_overlaps(&segments);
+            for backend in backends() {
+                assert_eq!(
+                    backend.count_overlaps(&segments),
+                    expected,
+                    "{}",
+                    backend.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sweep_handles_huge_sparse_maps() {
+        let big = 1_000_000_000;
+        let segments = vec![
+            Segment {
+                from: (-big, 5),
+                to: (big, 5),
+            },
+            Segment {
+                from: (10, 5),
+                to: (big + 10, 5),
+            },
+            Segment {
+                from: (7, -big),
+                to: (7, big),
+            },
+        ];
+        assert_eq!(choose(&segments).name(), "sweep");
+        assert!(!DenseGrid.fits(&segments));
+        assert_eq!(
+            DenseGrid.count_overlaps(&segments),
+            SweepLine.count_overlaps(&segments)
+        );
+        // x = 10..=big along y = 5, plus the vertical crossing at (7, 5)
+        assert_eq!(SweepLine.count_overlaps(&segments), (big - 10 + 2) as usize);
+    }
+
+    #[test]
+    fn sweep_handles_long_diagonals() {
+        let big = 1_000_000_000;
+        let segment = |from, to| Segment { from, to };
+        let segments = vec![
+            segment((-big, -big), (big, big)),
+            segment((0, 0), (2 * big, 2 * big)),
+            segment((-big, 5), (big, 5)),
+            segment((-big, big), (big, -big)),
+            segment((7, -big), (7, big)),
+        ];
+        // 0..=big along the shared diagonal, where every other crossing with
+        // it lands too, then (-5, 5), (7, 5) and (7, -7)
+        assert_eq!(SweepLine.count_overlaps(&segments), (big + 1 + 3) as usize);
+    }
+}
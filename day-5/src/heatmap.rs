@@ -0,0 +1,275 @@
+use crate::backend::DENSE_MAX_CELLS;
+use crate::parse;
+use crate::segment::Segment;
+use crate::Coord;
+
+// heat colours from one overlap up to the hottest cell
+const STOPS: [(u8, u8, u8); 4] = [(30, 60, 200), (0, 190, 190), (240, 220, 0), (230, 30, 30)];
+const OVERLAY: (u8, u8, u8) = (90, 90, 90);
+
+// Overlap counts over an inclusive rectangle, plus an optional overlay glyph
+// for every cell an input segment is drawn through.
+pub struct Heatmap {
+    pub min: Coord,
+    pub width: usize,
+    pub height: usize,
+    counts: Vec<u16>,
+    overlay: Option<Vec<Option<char>>>,
+}
+
+// `x0,y0,x1,y1`, corners in either order, bounded like segment coordinates
+pub fn parse_region(s: &str) -> Result<(Coord, Coord), String> {
+    let n = s
+        .split(',')
+        .map(parse::parse_number)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| format!("region {:?}: {}", s, error))?;
+    if n.len() != 4 {
+        return Err(format!("region {:?} is not x0,y0,x1,y1", s));
+    }
+    Ok((
+        (n[0].min(n[2]), n[1].min(n[3])),
+        (n[0].max(n[2]), n[1].max(n[3])),
+    ))
+}
+
+impl Heatmap {
+    // `counted` feeds the overlap counts; `overlay`, if any, is drawn on top
+    pub fn build(
+        counted: &[Segment],
+        overlay: Option<&[Segment]>,
+        (min, max): (Coord, Coord),
+    ) -> Result<Heatmap, String> {
+        let width = (max.0 - min.0 + 1) as u64;
+        let height = (max.1 - min.1 + 1) as u64;
+        if width
+            .checked_mul(height)
+            .is_none_or(|area| area > DENSE_MAX_CELLS)
+        {
+            return Err(format!(
+                "a {}x{} heatmap is too large; crop it",
+                width, height
+            ));
+        }
+        let mut map = Heatmap {
+            min,
+            width: width as usize,
+            height: height as usize,
+            counts: vec![0; (width * height) as usize],
+            overlay: None,
+        };
+        for segment in counted {
+            for point in segment.points() {
+                if let Some(i) = map.index(point) {
+                    map.counts[i] = map.counts[i].saturating_add(1);
+                }
+            }
+        }
+        if let Some(segments) = overlay {
+            let mut glyphs = vec![None; map.counts.len()];
+            for segment in segments {
+                let glyph = glyph(segment);
+                for point in line(segment) {
+                    if let Some(i) = map.index(point) {
+                        glyphs[i] = Some(glyph);
+                    }
+                }
+            }
+            map.overlay = Some(glyphs);
+        }
+        Ok(map)
+    }
+
+    fn index(&self, (x, y): Coord) -> Option<usize> {
+        let (dx, dy) = (x - self.min.0, y - self.min.1);
+        if dx < 0 || dy < 0 || dx as usize >= self.width || dy as usize >= self.height {
+            None
+        } else {
+            Some(dy as usize * self.width + dx as usize)
+        }
+    }
+
+    pub fn max(&self) -> u16 {
+        self.counts.iter().cloned().max().unwrap_or(0)
+    }
+
+    fn overlay_at(&self, i: usize) -> Option<char> {
+        self.overlay.as_ref().and_then(|glyphs| glyphs[i])
+    }
+
+    // Scales counts to 0..=255; uncounted cells under the overlay are dim grey
+    pub fn to_pgm(&self) -> String {
+        let max = self.max().max(1) as u32;
+        self.netpbm("P2", |i, count| {
+            if count == 0 && self.overlay_at(i).is_some() {
+                "40".to_string()
+            } else {
+                (count as u32 * 255 / max).to_string()
+            }
+        })
+    }
+
+    pub fn to_ppm(&self) -> String {
+        let max = self.max();
+        self.netpbm("P3", |i, count| {
+            let (r, g, b) = match (count, self.overlay_at(i)) {
+                (0, Some(_)) => OVERLAY,
+                (0, None) => (0, 0, 0),
+                _ => colour(count, max),
+            };
+            format!("{} {} {}", r, g, b)
+        })
+    }
+
+    fn netpbm(&self, magic: &str, pixel: impl Fn(usize, u16) -> String) -> String {
+        let mut out = format!("{}\n{} {}\n255\n", magic, self.width, self.height);
+        for row in 0..self.height {
+            let line = (0..self.width)
+                .map(|col| {
+                    let i = row * self.width + col;
+                    pixel(i, self.counts[i])
+                })
+                .collect::<Vec<_>>();
+            out.push_str(&line.join(" "));
+            out.push('\n');
+        }
+        out
+    }
+
+    // One character per cell, with the heat colour as its background: the
+    // count (`+` past 9), the overlay glyph, or `.` for an empty cell.
+    pub fn to_ansi(&self) -> String {
+        let max = self.max();
+        let mut out = String::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let i = row * self.width + col;
+                match (self.counts[i], self.overlay_at(i)) {
+                    (0, Some(glyph)) => {
+                        let (r, g, b) = OVERLAY;
+                        out.push_str(&format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, glyph))
+                    }
+                    (0, None) => out.push('.'),
+                    (count, _) => {
+                        let (r, g, b) = colour(count, max);
+                        let c = if count > 9 {
+                            '+'
+                        } else {
+                            (b'0' + count as u8) as char
+                        };
+                        out.push_str(&format!("\x1b[30;48;2;{};{};{}m{}\x1b[0m", r, g, b, c))
+                    }
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+// interpolates along STOPS, with 1 at the first stop and `max` at the last
+fn colour(count: u16, max: u16) -> (u8, u8, u8) {
+    if max <= 1 {
+        return STOPS[0];
+    }
+    let t = (count - 1) as f64 / (max - 1) as f64 * (STOPS.len() - 1) as f64;
+    let i = (t.floor() as usize).min(STOPS.len() - 2);
+    let f = t - i as f64;
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * f).round() as u8;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+// y grows downwards, as it's printed
+fn glyph(segment: &Segment) -> char {
+    let (dx, dy) = (segment.to.0 - segment.from.0, segment.to.1 - segment.from.1);
+    if dx == 0 {
+        '|'
+    } else if dy == 0 {
+        '-'
+    } else if (dx > 0) == (dy > 0) {
+        '\\'
+    } else {
+        '/'
+    }
+}
+
+// Bresenham: every cell the drawn line passes through, not just the lattice
+// points `Segment::points` counts
+fn line(segment: &Segment) -> Vec<Coord> {
+    let (mut x, mut y) = segment.from;
+    let (x1, y1) = segment.to;
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+    let mut err = dx + dy;
+    let mut cells = vec![(x, y)];
+    while (x, y) != (x1, y1) {
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        cells.push((x, y));
+    }
+    cells
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example() -> Vec<Segment> {
+        crate::parse::parse_segments(include_str!("test-input.txt")).unwrap()
+    }
+
+    #[test]
+    fn pgm_matches_puzzle_diagram() {
+        let segments = example();
+        let map = Heatmap::build(&segments, None, ((0, 0), (9, 9))).unwrap();
+        let pgm = map.to_pgm();
+        let mut lines = pgm.lines();
+        assert_eq!(lines.next(), Some("P2"));
+        assert_eq!(lines.next(), Some("10 10"));
+        assert_eq!(lines.next(), Some("255"));
+        // first row of the example diagram is 1.1....11.
+        assert_eq!(lines.next(), Some("85 0 85 0 0 0 0 85 85 0"));
+        assert_eq!(map.max(), 3);
+    }
+
+    #[test]
+    fn crop_and_overlay() {
+        let segments = [Segment {
+            from: (-1, -1),
+            to: (5, 2),
+        }];
+        let map = Heatmap::build(&[], Some(&segments), ((0, 0), (3, 1))).unwrap();
+        assert_eq!(map.to_ansi().matches('\\').count(), 4);
+        assert_eq!(
+            line(&segments[0]).first().zip(line(&segments[0]).last()),
+            Some((&(-1, -1), &(5, 2)))
+        );
+        assert!(Heatmap::build(&segments, None, ((0, 0), (1 << 20, 1 << 20))).is_err());
+    }
+
+    #[test]
+    fn regions() {
+        assert_eq!(parse_region("3,-1, 0,2"), Ok(((0, -1), (3, 2))));
+        assert!(parse_region("0,0,1").is_err());
+        assert!(parse_region("0,x,1,1").is_err());
+        assert!(parse_region("-9000000000000000000,0,9000000000000000000,1").is_err());
+        let edge = parse::MAX_COORD;
+        let region = parse_region(&format!("{},{},{},{}", -edge, -edge, edge, edge)).unwrap();
+        assert!(Heatmap::build(&[], None, region).is_err());
+    }
+
+    #[test]
+    fn colour_scale() {
+        assert_eq!(colour(1, 5), STOPS[0]);
+        assert_eq!(colour(5, 5), STOPS[3]);
+        assert_eq!(colour(1, 1), STOPS[0]);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::segment::Segment;
+use crate::Coord;
+use std::collections::HashMap;
+
+// A line through the lattice: its primitive step, pointing right (or down
+// when vertical), and the cross product of that step with any point on it.
+// Single points count as vertical, as they do everywhere else.
+pub type LineKey = (i64, i64, i128);
+
+// Positions along a line are x, or y on a vertical line, so consecutive
+// lattice points on it are `stride` apart.
+fn place(segment: &Segment) -> (LineKey, i64, i64, i64) {
+    let (from, to) = (segment.from, segment.to);
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let g = gcd(dx.unsigned_abs(), dy.unsigned_abs()).max(1) as i64;
+    let (mut sx, mut sy) = if dx == 0 { (0, 1) } else { (dx / g, dy / g) };
+    if sx < 0 {
+        sx = -sx;
+        sy = -sy;
+    }
+    let c = sx as i128 * from.1 as i128 - sy as i128 * from.0 as i128;
+    let (a, b, stride) = if sx == 0 {
+        (from.1, to.1, 1)
+    } else {
+        (from.0, to.0, sx)
+    };
+    ((sx, sy, c), a.min(b), a.max(b), stride)
+}
+
+pub fn key(segment: &Segment) -> LineKey {
+    place(segment).0
+}
+
+pub fn is_axis((sx, sy, _): &LineKey) -> bool {
+    *sx == 0 || *sy == 0
+}
+
+fn position((sx, _, _): &LineKey, point: Coord) -> i64 {
+    if *sx == 0 {
+        point.1
+    } else {
+        point.0
+    }
+}
+
+fn point((sx, sy, c): &LineKey, position: i64) -> Coord {
+    if *sx == 0 {
+        (-*c as i64, position)
+    } else {
+        let y = (c + *sy as i128 * position as i128) / *sx as i128;
+        (position, y as i64)
+    }
+}
+
+// How many segments cover each run of a line: `runs` holds the position
+// each depth starts at, up to the next entry.
+struct Coverage {
+    stride: i64,
+    runs: Vec<(i64, u64)>,
+}
+
+impl Coverage {
+    fn depth(&self, position: i64) -> u64 {
+        match self.runs.partition_point(|(at, _)| *at <= position) {
+            0 => 0,
+            i => self.runs[i - 1].1,
+        }
+    }
+}
+
+// Every segment grouped by the line it lies on, with the number of segments
+// over each stretch of that line, so overlaps along a line are counted from
+// its endpoints rather than point by point.
+pub struct Lines {
+    lines: HashMap<LineKey, Coverage>,
+}
+
+impl Lines {
+    pub fn build(segments: &[Segment]) -> Lines {
+        let mut events: HashMap<LineKey, (i64, Vec<(i64, i64)>)> = HashMap::new();
+        for segment in segments {
+            let (key, lo, hi, stride) = place(segment);
+            let (_, line) = events.entry(key).or_insert((stride, Vec::new()));
+            line.push((lo, 1));
+            line.push((hi + stride, -1));
+        }
+        let lines = events
+            .into_iter()
+            .map(|(key, (stride, mut line))| {
+                line.sort_unstable();
+                let mut runs: Vec<(i64, u64)> = Vec::new();
+                let mut depth = 0i64;
+                for (at, delta) in line {
+                    depth += delta;
+                    match runs.last_mut() {
+                        Some(last) if last.0 == at => last.1 = depth as u64,
+                        _ => runs.push((at, depth as u64)),
+                    }
+                }
+                (key, Coverage { stride, runs })
+            })
+            .collect();
+        Lines { lines }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &LineKey> {
+        self.lines.keys()
+    }
+
+    // segments on the line through `point`; 0 off it
+    pub fn depth(&self, key: &LineKey, point: Coord) -> u64 {
+        self.lines
+            .get(key)
+            .map_or(0, |line| line.depth(position(key, point)))
+    }
+
+    // each stretch of a line with a constant, nonzero number of segments
+    // over it, as the segment from its first lattice point to its last
+    pub fn runs(&self, key: &LineKey) -> Vec<(Segment, u64)> {
+        let line = &self.lines[key];
+        line.runs
+            .windows(2)
+            .filter(|pair| pair[0].1 > 0)
+            .map(|pair| {
+                let from = point(key, pair[0].0);
+                let to = point(key, pair[1].0 - line.stride);
+                (Segment { from, to }, pair[0].1)
+            })
+            .collect()
+    }
+
+    // lattice points on the line covered by two or more segments
+    pub fn dangerous(&self, key: &LineKey) -> u64 {
+        let line = &self.lines[key];
+        line.runs
+            .windows(2)
+            .filter(|pair| pair[0].1 > 1)
+            .map(|pair| ((pair[1].0 - pair[0].0) / line.stride) as u64)
+            .sum()
+    }
+}
+
+// Every lattice point where a segment of `a` meets a segment of `b` on a
+// different line, with the lines of all the segments seen meeting there.
+// This is one check per pair, so it suits a few hundred segments spread over
+// a huge area rather than a crowded one.
+pub fn crossings(a: &[Segment], b: &[Segment]) -> HashMap<Coord, Vec<LineKey>> {
+    let mut found: HashMap<Coord, Vec<LineKey>> = HashMap::new();
+    for first in a {
+        let first_key = key(first);
+        for second in b {
+            let second_key = key(second);
+            if first_key == second_key {
+                continue;
+            }
+            if let Some(at) = crossing(first, second) {
+                let keys = found.entry(at).or_default();
+                keys.push(first_key);
+                keys.push(second_key);
+            }
+        }
+    }
+    for keys in found.values_mut() {
+        keys.sort_unstable();
+        keys.dedup();
+    }
+    found
+}
+
+// where two segments on different lines share a lattice point, if they do
+fn crossing(a: &Segment, b: &Segment) -> Option<Coord> {
+    let (da, db) = (delta(a), delta(b));
+    let cross = |u: (i128, i128), v: (i128, i128)| u.0 * v.1 - u.1 * v.0;
+    let denom = cross(da, db);
+    if denom == 0 {
+        // parallel, or one of them is a single point
+        return [b.from, a.from]
+            .iter()
+            .find(|p| a.contains(**p) && b.contains(**p))
+            .cloned();
+    }
+    let offset = ((b.from.0 - a.from.0) as i128, (b.from.1 - a.from.1) as i128);
+    // a.from + da * s / denom == b.from + db * t / denom
+    let (s, t) = (cross(offset, db), cross(offset, da));
+    let in_range = |n: i128| {
+        if denom > 0 {
+            (0..=denom).contains(&n)
+        } else {
+            (denom..=0).contains(&n)
+        }
+    };
+    if !in_range(s) || !in_range(t) {
+        return None;
+    }
+    let (x, y) = (da.0 * s, da.1 * s);
+    if x % denom != 0 || y % denom != 0 {
+        return None;
+    }
+    let at = (a.from.0 + (x / denom) as i64, a.from.1 + (y / denom) as i64);
+    // the lines may meet between b's lattice points
+    if b.contains(at) {
+        Some(at)
+    } else {
+        None
+    }
+}
+
+fn delta(segment: &Segment) -> (i128, i128) {
+    (
+        (segment.to.0 - segment.from.0) as i128,
+        (segment.to.1 - segment.from.1) as i128,
+    )
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
@@ -1,99 +1,156 @@
-use std::collections::HashMap;
-use std::ops::RangeInclusive;
+mod backend;
+mod heatmap;
+mod lines;
+mod parse;
+mod query;
+mod segment;
 
-type Coord = (usize, usize);
+use segment::SegmentKind;
+
+type Coord = (i64, i64);
 
 fn main() {
     let input = include_str!("input.txt");
-    let mut map: HashMap<Coord, usize> = HashMap::new();
-
-    let pairs = input
-        .split("\n")
-        .map(|line| {
-            let coords = line.split(" -> ").collect::<Vec<_>>();
-            let from = parse_pair(&coords[0]);
-            let to = parse_pair(&coords[1]);
-            (from, to)
-        })
-        .collect::<Vec<_>>();
-
-    for (from, to) in pairs.iter() {
-        fill_line(&mut map, *from, *to);
+    let segments = parse::parse_segments(input).unwrap_or_else(|errors| {
+        for error in errors {
+            eprintln!("{}", error);
+        }
+        std::process::exit(1);
+    });
+
+    // `--backend hash|dense|sweep` overrides the automatic choice
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut forced = None;
+    if let Some(i) = args.iter().position(|a| a == "--backend") {
+        let name = args.get(i + 1).map(|s| s.as_str()).unwrap_or("");
+        forced = Some(backend::by_name(name).unwrap_or_else(|| {
+            eprintln!("unknown backend {:?}; expected hash, dense or sweep", name);
+            std::process::exit(1);
+        }));
+        args.drain(i..(i + 2).min(args.len()));
     }
 
-    let straight_intersections = map.values().filter(|val| **val > 1).count();
-
-    println!("part 1: {}", straight_intersections);
-
-    for (from, to) in pairs {
-        fill_diagonal(&mut map, from, to);
+    // `cargo run -- heatmap <pgm|ppm|ansi> [x0,y0,x1,y1] [--overlay]` draws the
+    // part 2 overlap counts instead of printing the answers, optionally with
+    // every input segment drawn over the top
+    if args.first().map(|s| s.as_str()) == Some("heatmap") {
+        let overlay = args.iter().any(|a| a == "--overlay");
+        args.retain(|a| a != "--overlay");
+        let counted = segments
+            .iter()
+            .filter(|s| s.kind() != SegmentKind::Other)
+            .cloned()
+            .collect::<Vec<_>>();
+        let region = match args.get(2) {
+            Some(region) => heatmap::parse_region(region),
+            None => backend::bounds(&segments).ok_or_else(|| "no segments".to_string()),
+        };
+        let map = region
+            .and_then(|region| {
+                heatmap::Heatmap::build(&counted, overlay.then_some(&segments[..]), region)
+            })
+            .unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            });
+        match args.get(1).map(|s| s.as_str()) {
+            Some("pgm") => print!("{}", map.to_pgm()),
+            Some("ppm") => print!("{}", map.to_ppm()),
+            Some("ansi") | None => print!("{}", map.to_ansi()),
+            Some(other) => {
+                eprintln!("unknown format {:?}; expected pgm, ppm or ansi", other);
+                std::process::exit(1);
+            }
+        }
+        return;
     }
 
-    let all_intersections = map.values().filter(|val| **val > 1).count();
+    let count = |include: &dyn Fn(SegmentKind) -> bool| {
+        let segments = segments
+            .iter()
+            .filter(|s| include(s.kind()))
+            .cloned()
+            .collect::<Vec<_>>();
+        match &forced {
+            Some(backend) if !backend.fits(&segments) => {
+                eprintln!(
+                    "the {} backend can't hold this map; try another",
+                    backend.name()
+                );
+                std::process::exit(1);
+            }
+            Some(backend) => backend.count_overlaps(&segments),
+            None => backend::choose(&segments).count_overlaps(&segments),
+        }
+    };
 
-    // for y in 0..10 {
-    //     for x in 0..10 {
-    //         match map.get(&(x, y)) {
-    //             None => {
-    //                 print!(".")
-    //             }
-    //             Some(t) => {
-    //                 print!("{}", *t)
-    //             }
-    //         }
-    //     }
-    //     print!("\n");
-    // }
+    let straight_intersections = count(&|kind| kind == SegmentKind::Axis);
 
-    println!("part 2: {}", all_intersections);
-}
+    println!("part 1: {}", straight_intersections);
 
-fn parse_pair(s: &str) -> Coord {
-    let pair = s.split(",").collect::<Vec<_>>();
-    (
-        pair[0].parse::<usize>().unwrap(),
-        pair[1].parse::<usize>().unwrap(),
-    )
-}
+    let all_intersections = count(&|kind| kind != SegmentKind::Other);
 
-fn fill_line(mut map: &mut HashMap<Coord, usize>, from: Coord, to: Coord) {
-    if from.0 == to.0 {
-        for y in abs_range(from.1, to.1) {
-            increment_count(&mut map, (from.0, y))
-        }
-    } else if from.1 == to.1 {
-        for x in abs_range(from.0, to.0) {
-            increment_count(&mut map, (x, from.1))
-        }
-    }
-}
+    println!("part 2: {}", all_intersections);
 
-fn fill_diagonal(mut map: &mut HashMap<Coord, usize>, from: Coord, to: Coord) {
-    if from.0 == to.0 || from.1 == to.1 {
-        return;
+    // `cargo run -- all` also counts lines at any other slope
+    if args.first().map(|s| s.as_str()) == Some("all") {
+        println!("any slope: {}", count(&|_| true));
     }
 
-    let distance = (from.0 as isize - to.0 as isize).abs() as usize;
-    let x_delta: isize = if from.0 <= to.0 { 1 } else { -1 };
-    let y_delta: isize = if from.1 <= to.1 { 1 } else { -1 };
-    let mut x = from.0 as isize;
-    let mut y = from.1 as isize;
-
-    for _ in 0..=distance {
-        increment_count(&mut map, (x as usize, y as usize));
-        x += x_delta;
-        y += y_delta;
+    // `cargo run -- backends` times every backend on the part 2 segments
+    if args.first().map(|s| s.as_str()) == Some("backends") {
+        let segments = segments
+            .iter()
+            .filter(|s| s.kind() != SegmentKind::Other)
+            .cloned()
+            .collect::<Vec<_>>();
+        println!("auto: {}", backend::choose(&segments).name());
+        for name in ["hash", "dense", "sweep"] {
+            let backend = backend::by_name(name).unwrap();
+            if !backend.fits(&segments) {
+                println!("{}: skipped, the map is too large", backend.name());
+                continue;
+            }
+            let start = std::time::Instant::now();
+            let overlaps = backend.count_overlaps(&segments);
+            println!("{}: {} in {:?}", backend.name(), overlaps, start.elapsed());
+        }
     }
-}
 
-fn increment_count(map: &mut HashMap<Coord, usize>, at: Coord) {
-    map.entry(at).and_modify(|count| *count += 1).or_insert(1);
-}
-
-fn abs_range(a: usize, b: usize) -> RangeInclusive<usize> {
-    if a <= b {
-        a..=b
-    } else {
-        b..=a
+    // `cargo run -- query <at|through> x,y`, `query rect x0,y0,x1,y1` or
+    // `query hottest` look things up in the part 2 map
+    if args.first().map(|s| s.as_str()) == Some("query") {
+        let counted = segments
+            .iter()
+            .filter(|s| s.kind() != SegmentKind::Other)
+            .cloned()
+            .collect::<Vec<_>>();
+        let map = query::VentMap::build(counted);
+        let arg = args.get(2).map(|s| s.as_str()).unwrap_or("");
+        let result = match args.get(1).map(|s| s.as_str()) {
+            Some("at") => {
+                parse::parse_pair(arg).map(|point| format!("{:?}: {}", point, map.count_at(point)))
+            }
+            Some("through") => parse::parse_pair(arg).map(|point| {
+                let through = map.segments_through(point);
+                let mut lines = vec![format!("{:?}: {} segments", point, through.len())];
+                lines.extend(through.iter().map(|s| s.to_string()));
+                lines.join("\n")
+            }),
+            Some("rect") => heatmap::parse_region(arg)
+                .map(|region| format!("dangerous in {:?}: {}", region, map.dangerous_in(region))),
+            Some("hottest") => Ok(match map.hottest() {
+                Some((point, count)) => format!("hottest: {:?} with {}", point, count),
+                None => "hottest: no vents".to_string(),
+            }),
+            _ => Err("expected query at, through, rect or hottest".to_string()),
+        };
+        match result {
+            Ok(text) => println!("{}", text),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
     }
 }
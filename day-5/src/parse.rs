@@ -0,0 +1,111 @@
+use crate::segment::Segment;
+use crate::Coord;
+use std::fmt;
+
+// Keeps every width, height and bounding-box area well inside i64/u64, so the
+// backends never have to think about overflow.
+pub const MAX_COORD: i64 = 1 << 40;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadLine {
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl fmt::Display for BadLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}: {:?}", self.line, self.reason, self.text)
+    }
+}
+
+// Lines look like `x1,y1 -> x2,y2` with signed coordinates. A single trailing
+// newline is fine; every other line that doesn't parse is reported by its
+// 1-based line number.
+pub fn parse_segments(input: &str) -> Result<Vec<Segment>, Vec<BadLine>> {
+    let input = input.strip_suffix('\n').unwrap_or(input);
+    let mut segments = Vec::new();
+    let mut bad = Vec::new();
+    for (i, text) in input.split('\n').enumerate() {
+        match parse_segment(text) {
+            Ok(segment) => segments.push(segment),
+            Err(reason) => bad.push(BadLine {
+                line: i + 1,
+                text: text.to_string(),
+                reason,
+            }),
+        }
+    }
+    if bad.is_empty() {
+        Ok(segments)
+    } else {
+        Err(bad)
+    }
+}
+
+fn parse_segment(text: &str) -> Result<Segment, String> {
+    let coords = text.split("->").collect::<Vec<_>>();
+    if coords.len() != 2 {
+        return Err("expected `x1,y1 -> x2,y2`".to_string());
+    }
+    Ok(Segment {
+        from: parse_pair(coords[0])?,
+        to: parse_pair(coords[1])?,
+    })
+}
+
+pub fn parse_pair(s: &str) -> Result<Coord, String> {
+    let pair = s.trim().split(',').collect::<Vec<_>>();
+    if pair.len() != 2 {
+        return Err(format!("expected `x,y`, found {:?}", s.trim()));
+    }
+    Ok((parse_number(pair[0])?, parse_number(pair[1])?))
+}
+
+pub fn parse_number(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    match s.parse::<i64>() {
+        Ok(n) if (-MAX_COORD..=MAX_COORD).contains(&n) => Ok(n),
+        Ok(_) => Err(format!("{} is outside ±{}", s, MAX_COORD)),
+        Err(_) => Err(format!("{:?} is not a number", s)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negative_coordinates() {
+        assert_eq!(
+            parse_segments("-3,4 -> 5,-6\n0,0 -> 0,2\n"),
+            Ok(vec![
+                Segment {
+                    from: (-3, 4),
+                    to: (5, -6)
+                },
+                Segment {
+                    from: (0, 0),
+                    to: (0, 2)
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        let errors = parse_segments(
+            "1,2 -> 3,4\n1,2 3,4\n1,2 -> 3\n\nx,1 -> 2,2\n1,2 -> 3,9999999999999\n\
+             -9223372036854775808,0 -> 0,0",
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![2, 3, 4, 5, 6, 7]
+        );
+        assert_eq!(
+            errors[3].to_string(),
+            r#"line 5: "x" is not a number: "x,1 -> 2,2""#
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::backend::{Backend, DenseGrid, Grid, SweepLine};
+use crate::lines::{self, Lines};
+use crate::segment::Segment;
+use crate::Coord;
+
+// The overlap map kept around for queries. Small maps use the dense grid plus
+// a summed-area table of dangerous cells (two or more overlaps), so any
+// rectangle is counted from four lookups. Maps whose bounding box is too big
+// for that, the ones the sweep backend is for, keep nothing but the segments:
+// a point is checked against each of them, and a rectangle is counted by
+// sweeping the segments clipped to it.
+pub struct VentMap {
+    segments: Vec<Segment>,
+    cells: Cells,
+    hottest: Option<(Coord, usize)>,
+}
+
+enum Cells {
+    // dangerous is (width + 1) x (height + 1); entry (x, y) covers cells < x
+    // and < y
+    Dense { grid: Grid, dangerous: Vec<u64> },
+    Sparse,
+}
+
+impl VentMap {
+    pub fn build(segments: Vec<Segment>) -> VentMap {
+        let (cells, hottest) = match DenseGrid::fill(&segments) {
+            Some(grid) => {
+                let (width, height) = (grid.width, grid.height);
+                let mut dangerous = vec![0u64; (width + 1) * (height + 1)];
+                let mut hottest: Option<(Coord, usize)> = None;
+                for y in 0..height {
+                    let mut row = 0;
+                    for x in 0..width {
+                        let count = grid.counts[y * width + x] as usize;
+                        if count > 1 {
+                            row += 1;
+                        }
+                        let above = dangerous[y * (width + 1) + x + 1];
+                        dangerous[(y + 1) * (width + 1) + x + 1] = above + row;
+                        if count > 0 && hottest.is_none_or(|(_, best)| count > best) {
+                            let at = (grid.min.0 + x as i64, grid.min.1 + y as i64);
+                            hottest = Some((at, count));
+                        }
+                    }
+                }
+                (Cells::Dense { grid, dangerous }, hottest)
+            }
+            None => (Cells::Sparse, sparse_hottest(&segments)),
+        };
+        VentMap {
+            segments,
+            cells,
+            hottest,
+        }
+    }
+
+    pub fn count_at(&self, point: Coord) -> usize {
+        match &self.cells {
+            Cells::Dense { grid, .. } => grid.get(point) as usize,
+            Cells::Sparse => self.segments_through(point).len(),
+        }
+    }
+
+    // cells with two or more overlaps in the inclusive rectangle
+    pub fn dangerous_in(&self, (from, to): (Coord, Coord)) -> u64 {
+        match &self.cells {
+            Cells::Dense { grid, dangerous } => {
+                let x0 = from.0.max(grid.min.0);
+                let y0 = from.1.max(grid.min.1);
+                let x1 = to.0.min(grid.max.0);
+                let y1 = to.1.min(grid.max.1);
+                if x0 > x1 || y0 > y1 {
+                    return 0;
+                }
+                let at = |x: i64, y: i64| {
+                    let (x, y) = ((x - grid.min.0) as usize, (y - grid.min.1) as usize);
+                    dangerous[y * (grid.width + 1) + x]
+                };
+                at(x1 + 1, y1 + 1) + at(x0, y0) - at(x0, y1 + 1) - at(x1 + 1, y0)
+            }
+            Cells::Sparse => {
+                let clipped = self
+                    .segments
+                    .iter()
+                    .filter_map(|segment| segment.clip((from, to)))
+                    .collect::<Vec<_>>();
+                SweepLine.count_overlaps(&clipped) as u64
+            }
+        }
+    }
+
+    // the highest count, first in reading order on a tie
+    pub fn hottest(&self) -> Option<(Coord, usize)> {
+        self.hottest
+    }
+
+    pub fn segments_through(&self, point: Coord) -> Vec<&Segment> {
+        self.segments
+            .iter()
+            .filter(|segment| segment.contains(point))
+            .collect()
+    }
+}
+
+// The most covered point is either somewhere along a single line, at the
+// first point in reading order of its most covered stretch, or where lines
+// cross, covered by everything on each of them.
+fn sparse_hottest(segments: &[Segment]) -> Option<(Coord, usize)> {
+    let lines = Lines::build(segments);
+    let along = lines.keys().flat_map(|key| {
+        lines.runs(key).into_iter().map(|(run, count)| {
+            let first = [run.from, run.to]
+                .iter()
+                .cloned()
+                .min_by_key(|(x, y)| (*y, *x))
+                .unwrap();
+            (first, count)
+        })
+    });
+    let crossing = lines::crossings(segments, segments)
+        .into_iter()
+        .map(|(at, keys)| (at, keys.iter().map(|key| lines.depth(key, at)).sum()));
+    along
+        .chain(crossing)
+        .max_by_key(|((x, y), count)| (*count, -*y, -*x))
+        .map(|(at, count)| (at, count as usize))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example() -> VentMap {
+        let segments = crate::parse::parse_segments(include_str!("test-input.txt")).unwrap();
+        VentMap::build(segments)
+    }
+
+    #[test]
+    fn point_queries() {
+        let map = example();
+        assert_eq!(map.count_at((7, 4)), 2);
+        assert_eq!(map.count_at((4, 4)), 3);
+        assert_eq!(map.count_at((-1, 4)), 0);
+        assert_eq!(map.hottest(), Some(((4, 4), 3)));
+        let through = map.segments_through((7, 4));
+        assert_eq!(
+            through.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+            vec!["9,4 -> 3,4", "7,0 -> 7,4"]
+        );
+    }
+
+    #[test]
+    fn rectangles_match_brute_force() {
+        let map = example();
+        assert_eq!(map.dangerous_in(((-5, -5), (20, 20))), 12);
+        for (from, to) in [
+            ((0, 0), (9, 9)),
+            ((2, 3), (6, 7)),
+            ((5, 0), (5, 9)),
+            ((4, 4), (4, 4)),
+        ] {
+            let expected = (from.1..=to.1)
+                .flat_map(|y| (from.0..=to.0).map(move |x| (x, y)))
+                .filter(|p| map.count_at(*p) > 1)
+                .count() as u64;
+            assert_eq!(map.dangerous_in((from, to)), expected, "{:?}", (from, to));
+        }
+        assert_eq!(map.dangerous_in(((20, 20), (30, 30))), 0);
+    }
+
+    #[test]
+    fn sparse_maps_answer_the_same() {
+        let far = 1 << 30;
+        let segments = crate::parse::parse_segments(include_str!("test-input.txt"))
+            .unwrap()
+            .into_iter()
+            .chain([Segment {
+                from: (far, far),
+                to: (far, far + 2),
+            }])
+            .collect::<Vec<_>>();
+        let map = VentMap::build(segments);
+        assert!(matches!(map.cells, Cells::Sparse));
+        assert_eq!(map.count_at((4, 4)), 3);
+        assert_eq!(map.count_at((far, far + 1)), 1);
+        assert_eq!(map.hottest(), Some(((4, 4), 3)));
+        let dense = example();
+        for (from, to) in [
+            ((0, 0), (9, 9)),
+            ((2, 3), (6, 7)),
+            ((5, 0), (5, 9)),
+            ((9, 9), (0, 0)),
+        ] {
+            assert_eq!(map.dangerous_in((from, to)), dense.dangerous_in((from, to)));
+        }
+    }
+
+    #[test]
+    fn huge_maps_are_not_filled_in() {
+        let big = 1_000_000_000;
+        let segment = |from, to| Segment { from, to };
+        let map = VentMap::build(vec![
+            segment((-big, 5), (big, 5)),
+            segment((10, 5), (big + 10, 5)),
+            segment((7, -big), (7, big)),
+            segment((-big, -big), (big, big)),
+        ]);
+        assert_eq!(map.count_at((7, 5)), 2);
+        assert_eq!(map.count_at((100, 5)), 2);
+        assert_eq!(map.hottest(), Some(((5, 5), 2)));
+        // (5, 5), (7, 5) and (7, 7), then the shared run along y = 5 from 10
+        assert_eq!(map.dangerous_in(((0, 0), (20, 20))), 3 + 11);
+        assert_eq!(
+            map.dangerous_in(((-big, -big), (big, big))),
+            3 + (big - 10) as u64 + 1
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use crate::Coord;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SegmentKind {
+    Axis,
+    Diagonal,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub from: Coord,
+    pub to: Coord,
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn div_floor(n: i64, d: i64) -> i64 {
+    let q = n / d;
+    if n % d != 0 && (n < 0) != (d < 0) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{},{} -> {},{}",
+            self.from.0, self.from.1, self.to.0, self.to.1
+        )
+    }
+}
+
+impl Segment {
+    fn delta(&self) -> (i64, i64) {
+        (self.to.0 - self.from.0, self.to.1 - self.from.1)
+    }
+
+    // a single point counts as an axis line, as it did before
+    pub fn kind(&self) -> SegmentKind {
+        let (dx, dy) = self.delta();
+        if dx == 0 || dy == 0 {
+            SegmentKind::Axis
+        } else if dx.abs() == dy.abs() {
+            SegmentKind::Diagonal
+        } else {
+            SegmentKind::Other
+        }
+    }
+
+    // Any integer point on the line between the ends is one of `points`, so
+    // this is just collinear and inside the bounding box.
+    pub fn contains(&self, (x, y): Coord) -> bool {
+        let (dx, dy) = self.delta();
+        let (px, py) = (x - self.from.0, y - self.from.1);
+        dx as i128 * py as i128 == dy as i128 * px as i128
+            && x >= self.from.0.min(self.to.0)
+            && x <= self.from.0.max(self.to.0)
+            && y >= self.from.1.min(self.to.1)
+            && y <= self.from.1.max(self.to.1)
+    }
+
+    // The part of the segment inside an inclusive rectangle, ends moved in to
+    // its first and last lattice points there; None if none are.
+    pub fn clip(&self, (min, max): (Coord, Coord)) -> Option<Segment> {
+        let (dx, dy) = self.delta();
+        let steps = gcd(dx.unsigned_abs(), dy.unsigned_abs()) as i64;
+        let step = if steps == 0 {
+            (0, 0)
+        } else {
+            (dx / steps, dy / steps)
+        };
+        // from + step * k for k in 0..=steps, kept inside on both axes
+        let (mut lo, mut hi) = (0, steps);
+        for (from, step, min, max) in [
+            (self.from.0, step.0, min.0, max.0),
+            (self.from.1, step.1, min.1, max.1),
+        ] {
+            if step == 0 {
+                if from < min || from > max {
+                    return None;
+                }
+                continue;
+            }
+            let (first, last) = if step > 0 { (min, max) } else { (max, min) };
+            lo = lo.max(-div_floor(from - first, step));
+            hi = hi.min(div_floor(last - from, step));
+        }
+        if lo > hi {
+            return None;
+        }
+        let at = |k: i64| (self.from.0 + step.0 * k, self.from.1 + step.1 * k);
+        Some(Segment {
+            from: at(lo),
+            to: at(hi),
+        })
+    }
+
+    // Every integer point on the segment, from `from` to `to`. Consecutive
+    // lattice points are (dx / g, dy / g) apart where g = gcd(|dx|, |dy|).
+    pub fn points(&self) -> impl Iterator<Item = Coord> {
+        let (dx, dy) = self.delta();
+        let steps = gcd(dx.unsigned_abs(), dy.unsigned_abs());
+        let (step_x, step_y) = if steps == 0 {
+            (0, 0)
+        } else {
+            (dx / steps as i64, dy / steps as i64)
+        };
+        let from = self.from;
+        (0..=steps as i64).map(move |i| (from.0 + step_x * i, from.1 + step_y * i))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn points(from: Coord, to: Coord) -> Vec<Coord> {
+        Segment { from, to }.points().collect()
+    }
+
+    #[test]
+    fn rasterise() {
+        assert_eq!(points((1, 1), (1, 3)), vec![(1, 1), (1, 2), (1, 3)]);
+        assert_eq!(points((9, 7), (7, 7)), vec![(9, 7), (8, 7), (7, 7)]);
+        assert_eq!(points((9, 7), (7, 9)), vec![(9, 7), (8, 8), (7, 9)]);
+        assert_eq!(points((0, 0), (6, 4)), vec![(0, 0), (3, 2), (6, 4)]);
+        assert_eq!(points((5, 1), (2, 0)), vec![(5, 1), (2, 0)]);
+        assert_eq!(points((4, 4), (4, 4)), vec![(4, 4)]);
+        assert_eq!(
+            points((-2, 1), (1, -2)),
+            vec![(-2, 1), (-1, 0), (0, -1), (1, -2)]
+        );
+    }
+
+    #[test]
+    fn clip() {
+        let rect = ((0, 0), (4, 3));
+        for (from, to) in [
+            ((-3, -3), (9, 9)),
+            ((9, 1), (-9, 1)),
+            ((2, 7), (2, -7)),
+            ((-5, 5), (7, -1)),
+            ((5, 0), (9, 9)),
+            ((1, 1), (1, 1)),
+        ] {
+            let segment = Segment { from, to };
+            let inside = segment
+                .points()
+                .filter(|p| p.0 >= 0 && p.0 <= 4 && p.1 >= 0 && p.1 <= 3)
+                .collect::<Vec<_>>();
+            let clipped = segment.clip(rect);
+            assert_eq!(
+                clipped.map(|s| s.points().collect::<Vec<_>>()),
+                if inside.is_empty() {
+                    None
+                } else {
+                    Some(inside)
+                },
+                "{}",
+                segment
+            );
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        let kind = |from, to| Segment { from, to }.kind();
+        assert_eq!(kind((0, 0), (0, 5)), SegmentKind::Axis);
+        assert_eq!(kind((0, 5), (5, 0)), SegmentKind::Diagonal);
+        assert_eq!(kind((0, 0), (6, 4)), SegmentKind::Other);
+    }
+
+    #[test]
+    fn contains_exactly_its_points() {
+        let segment = Segment {
+            from: (6, 4),
+            to: (-3, -2),
+        };
+        for x in -5..8 {
+            for y in -5..8 {
+                let on = segment.points().any(|p| p == (x, y));
+                assert_eq!(segment.contains((x, y)), on, "{:?}", (x, y));
+            }
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use crate::lifecycle::Lifecycle;
+use crate::series::lineages;
+
+// What was seen on day N: just the total, or the count at every timer value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Observation {
+    Total(u128),
+    Histogram(Vec<u128>),
+}
+
+impl Observation {
+    // `26` or `1,2,3,...` with one count per timer value
+    pub fn parse(s: &str) -> Result<Observation, String> {
+        let counts = s
+            .split(',')
+            .map(|n| n.trim().parse::<u128>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("{:?} is not a count or a list of counts", s))?;
+        if counts.len() == 1 {
+            Ok(Observation::Total(counts[0]))
+        } else {
+            Ok(Observation::Histogram(counts))
+        }
+    }
+}
+
+// Starting counts by timer value that give the observation, and whether the
+// search stopped at its limit with more possibly left to find
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solutions {
+    pub found: Vec<Vec<u64>>,
+    pub complete: bool,
+}
+
+// Day N's population is linear in the starting counts: one fish at timer t
+// becomes a fixed set of descendants, so the observation is the sum of those
+// sets weighted by how many fish started at each timer. The search picks a
+// count per timer in turn, never overshooting any observed value and never
+// leaving more than the remaining fish could make up.
+pub fn solve(
+    fish: &Lifecycle,
+    observation: &Observation,
+    days: u64,
+    max_fish: u64,
+    limit: usize,
+) -> Result<Solutions, String> {
+    let timers = (0..fish.timers()).collect::<Vec<_>>();
+    let overflow = || format!("day {}: too many fish for a u128", days);
+    let (target, outcomes) = match observation {
+        Observation::Total(total) => {
+            let each = lineages(fish, &timers, days, None).ok_or_else(overflow)?;
+            (vec![*total], each.into_iter().map(|n| vec![n]).collect())
+        }
+        Observation::Histogram(counts) => {
+            if counts.len() != fish.timers() {
+                return Err(format!(
+                    "expected {} counts, one per timer value, not {}",
+                    fish.timers(),
+                    counts.len()
+                ));
+            }
+            let outcomes = timers
+                .iter()
+                .map(|&t| {
+                    let one = fish.population(&[t]).ok()?;
+                    Some(fish.by_timer(&fish.advance(&one, days, None)?))
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(overflow)?;
+            (counts.clone(), outcomes)
+        }
+    };
+
+    // A full histogram usually pins the counts down: when the outcomes are
+    // independent mod P, any answer of fewer than P fish is the one solution
+    // mod P, so checking that one candidate settles it.
+    if target.len() == outcomes.len() && max_fish < P {
+        if let Some(counts) = solve_mod(&outcomes, &target) {
+            let fits = counts
+                .iter()
+                .try_fold(0u64, |sum, n| sum.checked_add(*n))
+                .is_some_and(|sum| sum <= max_fish)
+                && combine(&outcomes, &counts).as_ref() == Some(&target);
+            return Ok(Solutions {
+                found: if fits && limit > 0 {
+                    vec![counts]
+                } else {
+                    vec![]
+                },
+                complete: !(fits && limit == 0),
+            });
+        }
+    }
+
+    // best[t][i]: the most any one fish starting at timer t or later adds to
+    // observed value i
+    let mut best = vec![vec![0u128; target.len()]; outcomes.len() + 1];
+    for t in (0..outcomes.len()).rev() {
+        for i in 0..target.len() {
+            best[t][i] = best[t + 1][i].max(outcomes[t][i]);
+        }
+    }
+
+    let mut search = Search {
+        outcomes: &outcomes,
+        best: &best,
+        limit,
+        counts: Vec::new(),
+        solutions: Solutions {
+            found: Vec::new(),
+            complete: true,
+        },
+    };
+    search.descend(target, max_fish);
+    Ok(search.solutions)
+}
+
+// 2^61 - 1, so every product of two residues fits a u128
+const P: u64 = (1 << 61) - 1;
+
+// Solves sum over t of counts[t] * outcomes[t][i] = target[i] mod P by
+// Gaussian elimination; None when the outcomes aren't independent mod P.
+fn solve_mod(outcomes: &[Vec<u128>], target: &[u128]) -> Option<Vec<u64>> {
+    let n = outcomes.len();
+    let p = P as u128;
+    let pow = |mut base: u128, mut exp: u128| {
+        let mut result = 1;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base % p;
+            }
+            base = base * base % p;
+            exp >>= 1;
+        }
+        result
+    };
+    // row i is observed value i: its coefficient per timer, then the target
+    let mut rows = (0..n)
+        .map(|i| {
+            let mut row = outcomes.iter().map(|o| o[i] % p).collect::<Vec<_>>();
+            row.push(target[i] % p);
+            row
+        })
+        .collect::<Vec<_>>();
+    for col in 0..n {
+        let pivot = (col..n).find(|r| rows[*r][col] != 0)?;
+        rows.swap(col, pivot);
+        let inverse = pow(rows[col][col], p - 2);
+        for cell in rows[col].iter_mut() {
+            *cell = *cell * inverse % p;
+        }
+        let pivot_row = rows[col].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if r != col && factor != 0 {
+                for (cell, pivot) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *cell = (*cell + (p - factor) * pivot) % p;
+                }
+            }
+        }
+    }
+    Some(rows.iter().map(|row| row[n] as u64).collect())
+}
+
+// the observation `counts` starting fish would make; None on overflow
+fn combine(outcomes: &[Vec<u128>], counts: &[u64]) -> Option<Vec<u128>> {
+    let mut total = vec![0u128; outcomes[0].len()];
+    for (outcome, count) in outcomes.iter().zip(counts) {
+        for (sum, adds) in total.iter_mut().zip(outcome) {
+            *sum = sum.checked_add(adds.checked_mul(*count as u128)?)?;
+        }
+    }
+    Some(total)
+}
+
+struct Search<'a> {
+    outcomes: &'a [Vec<u128>],
+    best: &'a [Vec<u128>],
+    limit: usize,
+    counts: Vec<u64>,
+    solutions: Solutions,
+}
+
+impl Search<'_> {
+    fn descend(&mut self, left: Vec<u128>, fish_left: u64) {
+        let t = self.counts.len();
+        let reachable = left
+            .iter()
+            .zip(&self.best[t])
+            .all(|(need, most)| *need <= most.saturating_mul(fish_left as u128));
+        if !reachable || !self.solutions.complete {
+            return;
+        }
+        if t == self.outcomes.len() {
+            // reachable with nothing left to add means all zero
+            if self.solutions.found.len() == self.limit {
+                self.solutions.complete = false;
+            } else {
+                self.solutions.found.push(self.counts.clone());
+            }
+            return;
+        }
+        // The later timers can add at most best[t + 1][i] per fish, so this
+        // one has to cover whatever they can't: for each observed value i,
+        // left - count * adds <= (fish_left - count) * later.
+        let mut from = 0;
+        for (need, (adds, later)) in left
+            .iter()
+            .zip(self.outcomes[t].iter().zip(&self.best[t + 1]))
+        {
+            let short = need.saturating_sub(later.saturating_mul(fish_left as u128));
+            if short > 0 {
+                if adds <= later {
+                    return;
+                }
+                let least = short.div_ceil(adds - later).min(fish_left as u128 + 1);
+                from = from.max(least as u64);
+            }
+        }
+        if from > fish_left {
+            return;
+        }
+        let mut left = left;
+        for (need, adds) in left.iter_mut().zip(&self.outcomes[t]) {
+            match adds
+                .checked_mul(from as u128)
+                .and_then(|n| need.checked_sub(n))
+            {
+                Some(n) => *need = n,
+                None => return,
+            }
+        }
+        for count in from..=fish_left {
+            self.counts.push(count);
+            self.descend(left.clone(), fish_left - count);
+            self.counts.pop();
+            // one more fish at this timer
+            let mut fits = true;
+            for (need, adds) in left.iter_mut().zip(&self.outcomes[t]) {
+                match need.checked_sub(*adds) {
+                    Some(n) => *need = n,
+                    None => fits = false,
+                }
+            }
+            if !fits {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recovers_the_example() {
+        let fish = Lifecycle::default();
+        let start = fish.population(&[3, 4, 3, 1, 2]).unwrap();
+        let seen = fish.by_timer(&fish.advance(&start, 18, None).unwrap());
+        let solutions = solve(&fish, &Observation::Histogram(seen.clone()), 18, 10, 100).unwrap();
+        assert_eq!(
+            solutions,
+            Solutions {
+                found: vec![vec![0, 1, 1, 2, 1, 0, 0, 0, 0]],
+                complete: true
+            }
+        );
+        // one fish too many for the bound, and a histogram nothing makes
+        assert!(
+            solve(&fish, &Observation::Histogram(seen.clone()), 18, 4, 100)
+                .unwrap()
+                .found
+                .is_empty()
+        );
+        let mut off = seen;
+        off[0] += 1;
+        assert!(solve(&fish, &Observation::Histogram(off), 18, 10, 100)
+            .unwrap()
+            .found
+            .is_empty());
+    }
+
+    #[test]
+    fn totals_are_ambiguous() {
+        let fish = Lifecycle::default();
+        let solutions = solve(&fish, &Observation::Total(26), 18, 5, 1000).unwrap();
+        assert!(solutions.complete);
+        assert!(solutions.found.contains(&vec![0, 1, 1, 2, 1, 0, 0, 0, 0]));
+        assert!(solutions.found.len() > 1);
+        for counts in &solutions.found {
+            let timers = counts
+                .iter()
+                .enumerate()
+                .flat_map(|(t, n)| std::iter::repeat_n(t, *n as usize))
+                .collect::<Vec<_>>();
+            let start = fish.population(&timers).unwrap();
+            let total = fish.advance(&start, 18, None).unwrap().iter().sum::<u128>();
+            assert_eq!(total, 26, "{:?}", counts);
+        }
+
+        let capped = solve(&fish, &Observation::Total(26), 18, 5, 1).unwrap();
+        assert_eq!((capped.found.len(), capped.complete), (1, false));
+        assert!(solve(&fish, &Observation::Total(1), 18, 2, 100)
+            .unwrap()
+            .found
+            .is_empty());
+    }
+}
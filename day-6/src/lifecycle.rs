@@ -0,0 +1,252 @@
+use crate::matrix::{add_mul, Matrix};
+use crate::Population;
+use std::fmt;
+
+// bigger than this and the transition matrix stops being cheap to square
+const MAX_STATES: usize = 1024;
+
+// How a species lives: after spawning a fish's timer goes back to `reset`,
+// each spawn adds `spawn` newborns with their timer at `newborn`, and with a
+// `mortality` age every fish dies once it has lived that many days. Fish in
+// the input are taken to be age 0 on day 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lifecycle {
+    pub reset: usize,
+    pub newborn: usize,
+    pub spawn: u128,
+    pub mortality: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    ZeroMortality,
+    TooManyStates { states: usize },
+    BadTimer { timer: usize, max: usize },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::ZeroMortality => write!(f, "mortality age must be at least 1"),
+            ConfigError::TooManyStates { states } => write!(
+                f,
+                "lifecycle needs {} states, more than the {} allowed",
+                states, MAX_STATES
+            ),
+            ConfigError::BadTimer { timer, max } => {
+                write!(
+                    f,
+                    "timer {} is past this lifecycle's longest, {}",
+                    timer, max
+                )
+            }
+        }
+    }
+}
+
+impl Default for Lifecycle {
+    // lanternfish
+    fn default() -> Lifecycle {
+        Lifecycle {
+            reset: 6,
+            newborn: 8,
+            spawn: 1,
+            mortality: None,
+        }
+    }
+}
+
+impl Lifecycle {
+    pub fn new(
+        reset: usize,
+        newborn: usize,
+        spawn: u128,
+        mortality: Option<usize>,
+    ) -> Result<Lifecycle, ConfigError> {
+        if mortality == Some(0) {
+            return Err(ConfigError::ZeroMortality);
+        }
+        let states = reset
+            .max(newborn)
+            .checked_add(1)
+            .and_then(|timers| timers.checked_mul(mortality.unwrap_or(1)));
+        match states {
+            Some(states) if states <= MAX_STATES => Ok(Lifecycle {
+                reset,
+                newborn,
+                spawn,
+                mortality,
+            }),
+            _ => Err(ConfigError::TooManyStates {
+                states: states.unwrap_or(usize::MAX),
+            }),
+        }
+    }
+
+    pub fn timers(&self) -> usize {
+        self.reset.max(self.newborn) + 1
+    }
+
+    fn ages(&self) -> usize {
+        self.mortality.unwrap_or(1)
+    }
+
+    // Population holds a count per (age, timer); without mortality there is
+    // only the one age
+    pub fn states(&self) -> usize {
+        self.timers() * self.ages()
+    }
+
+    pub fn index(&self, age: usize, timer: usize) -> usize {
+        age * self.timers() + timer
+    }
+
+    pub fn population(&self, timers: &[usize]) -> Result<Population, ConfigError> {
+        let mut population = vec![0; self.states()];
+        for &timer in timers {
+            if timer >= self.timers() {
+                return Err(ConfigError::BadTimer {
+                    timer,
+                    max: self.timers() - 1,
+                });
+            }
+            population[self.index(0, timer)] += 1;
+        }
+        Ok(population)
+    }
+
+    // counts by timer value, whatever the age
+    pub fn by_timer(&self, population: &Population) -> Vec<u128> {
+        let mut timers = vec![0; self.timers()];
+        for (state, count) in population.iter().enumerate() {
+            timers[state % self.timers()] += count;
+        }
+        timers
+    }
+
+    // where one fish in `state` is a day later, and how many of it there are;
+    // a fish reaching its mortality age dies before it can spawn again
+    fn successors(&self, state: usize) -> Vec<(usize, u128)> {
+        let (age, timer) = (state / self.timers(), state % self.timers());
+        let age = if self.mortality.is_some() { age + 1 } else { 0 };
+        if age >= self.ages() {
+            return vec![];
+        }
+        let mut next = vec![(
+            self.index(age, if timer == 0 { self.reset } else { timer - 1 }),
+            1,
+        )];
+        if timer == 0 && self.spawn > 0 {
+            next.push((self.index(0, self.newborn), self.spawn));
+        }
+        next
+    }
+
+    // one day at a time; None if a plain count overflows
+    pub fn step(&self, population: &Population, modulus: Option<u64>) -> Option<Population> {
+        let mut next = vec![0u128; self.states()];
+        for (state, count) in population.iter().enumerate() {
+            for (to, times) in self.successors(state) {
+                next[to] = add_mul(next[to], *count, times, modulus)?;
+            }
+        }
+        Some(next)
+    }
+
+    // next[i] = sum of m[i][j] * now[j]
+    pub fn matrix(&self) -> Matrix {
+        let mut m = Matrix::zero(self.states());
+        for state in 0..self.states() {
+            for (to, times) in self.successors(state) {
+                *m.at(to, state) += times;
+            }
+        }
+        m
+    }
+
+    // Steps day by day unless squaring the matrix is cheaper: that's n^3 work
+    // per product against n per day.
+    pub fn advance(
+        &self,
+        population: &Population,
+        days: u64,
+        modulus: Option<u64>,
+    ) -> Option<Population> {
+        let n = self.states() as u128;
+        let squarings = (64 - days.leading_zeros()) as u128;
+        if (days as u128) * n <= 2 * n * n * n * squarings {
+            let mut population = population.clone();
+            for _ in 0..days {
+                population = self.step(&population, modulus)?;
+            }
+            Some(population)
+        } else {
+            self.matrix().pow(days, modulus)?.apply(population, modulus)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_is_lanternfish() {
+        let fish = Lifecycle::default();
+        let start = fish.population(&[3, 4, 3, 1, 2]).unwrap();
+        let total = |days| {
+            fish.advance(&start, days, None)
+                .unwrap()
+                .iter()
+                .sum::<u128>()
+        };
+        assert_eq!(total(18), 26);
+        assert_eq!(total(80), 5934);
+        assert_eq!(total(256), 26984457539);
+    }
+
+    #[test]
+    fn mortality_and_spawn_count() {
+        // spawns 2 every other day and dies at age 3: one fish at timer 0
+        // has 2 young on day 1 and dies on day 3, as the young have 4 more;
+        // the young die on day 4
+        let fish = Lifecycle::new(1, 1, 2, Some(3)).unwrap();
+        let start = fish.population(&[0]).unwrap();
+        let totals = (0..5)
+            .map(|days| {
+                fish.advance(&start, days, None)
+                    .unwrap()
+                    .iter()
+                    .sum::<u128>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(totals, vec![1, 3, 3, 6, 4]);
+        // stepping and the matrix agree
+        for days in 0..40 {
+            assert_eq!(
+                fish.advance(&start, days, Some(1_000_000_007)),
+                fish.matrix()
+                    .pow(days, Some(1_000_000_007))
+                    .unwrap()
+                    .apply(&start, Some(1_000_000_007))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_bad_configs() {
+        assert_eq!(
+            Lifecycle::new(6, 8, 1, Some(0)),
+            Err(ConfigError::ZeroMortality)
+        );
+        assert_eq!(
+            Lifecycle::new(6, 8, 1, Some(200)),
+            Err(ConfigError::TooManyStates { states: 1800 })
+        );
+        assert!(Lifecycle::new(usize::MAX, 0, 1, None).is_err());
+        assert_eq!(
+            Lifecycle::new(2, 3, 1, None).unwrap().population(&[4]),
+            Err(ConfigError::BadTimer { timer: 4, max: 3 })
+        );
+    }
+}
@@ -1,56 +1,178 @@
-use std::collections::HashMap;
+mod inverse;
+mod lifecycle;
+mod matrix;
+mod series;
+
+use lifecycle::Lifecycle;
+use std::convert::TryFrom;
+
+// fish counts by lifecycle state; see Lifecycle::index
+type Population = Vec<u128>;
 
 fn main() {
     // let input = "3,4,3,1,2";
     let input = include_str!("input.txt");
-    let mut population = HashMap::new();
 
-    for s in input.split(",") {
-        let age = s.parse::<usize>().unwrap();
-        increment_count(&mut population, age, 1)
-    }
+    // `--reset`, `--newborn`, `--spawn` and `--mortality` change the species;
+    // the defaults are lanternfish
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut flag = |name: &str| {
+        let i = args.iter().position(|a| a == name)?;
+        let value = args.get(i + 1).and_then(|s| s.parse::<u128>().ok());
+        args.drain(i..(i + 2).min(args.len()));
+        Some(value.unwrap_or_else(|| {
+            eprintln!("{} needs a number", name);
+            std::process::exit(1);
+        }))
+    };
+    let default = Lifecycle::default();
+    // anything past usize is rejected by Lifecycle::new as too many states
+    let size = |n: u128| usize::try_from(n).unwrap_or(usize::MAX);
+    let reset = flag("--reset").map_or(default.reset, size);
+    let newborn = flag("--newborn").map_or(default.newborn, size);
+    let spawn = flag("--spawn").unwrap_or(default.spawn);
+    let mortality = flag("--mortality").map(size);
+    let timers = parse(input);
+    let fish = Lifecycle::new(reset, newborn, spawn, mortality)
+        .and_then(|fish| fish.population(&timers).map(|p| (fish, p)));
+    let (fish, population) = fish.unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    });
 
-    for _ in 0..80 {
-        generation(&mut population);
+    // `cargo run -- series <days> [--mod <m>]` prints a CSV of every day's
+    // counts by timer, and `lineage <days> [--mod <m>]` one of each input
+    // fish's share of the final population, instead of the answers
+    let mode = args.first().map(|s| s.as_str());
+    if mode == Some("series") || mode == Some("lineage") {
+        let days = args.get(1).and_then(|s| s.parse::<u64>().ok());
+        let days = days.unwrap_or_else(|| {
+            eprintln!("usage: {} <days> [--mod <m>]", mode.unwrap());
+            std::process::exit(1);
+        });
+        let modulus = modulus_arg(args.get(2..).unwrap_or(&[]));
+        let csv = if mode == Some("series") {
+            series::time_series(&fish, &population, days, modulus)
+        } else {
+            series::lineages(&fish, &timers, days, modulus).map(|counts| {
+                let mut lines = vec!["fish,timer,descendants".to_string()];
+                for (i, (timer, count)) in timers.iter().zip(counts).enumerate() {
+                    lines.push(format!("{},{},{}", i, timer, count));
+                }
+                lines.join("\n")
+            })
+        };
+        match csv {
+            Some(csv) => println!("{}", csv),
+            None => {
+                eprintln!("day {}: too many fish for a u128; try --mod", days);
+                std::process::exit(1);
+            }
+        }
+        return;
     }
-    println!("part 1: {}", total_pop(&population));
 
-    for _ in 80..256 {
-        generation(&mut population);
+    let total = |days| fish.advance(&population, days, None).map(|p| total_pop(&p));
+    for (part, days) in [(1, 80), (2, 256)] {
+        match total(days) {
+            Some(total) => println!("part {}: {}", part, total),
+            None => println!("part {}: too many fish for a u128", part),
+        }
     }
 
-    println!("part 2: {}", total_pop(&population));
-}
-
-fn generation(mut population: &mut HashMap<usize, u64>) {
-    let mut spawn_count = 0;
-
-    let mut next_map = HashMap::new();
-
-    for (age, count) in population.iter() {
-        if *age == 0 {
-            spawn_count += *count;
-            increment_count(&mut next_map, 6, *count);
-        } else {
-            increment_count(&mut next_map, *age - 1, *count);
+    // `cargo run -- days <n> [--mod <m>]` jumps straight to day n; past about
+    // day 1000 the count no longer fits in a u128 and needs a modulus
+    if args.first().map(|s| s.as_str()) == Some("days") {
+        let days = args.get(1).and_then(|s| s.parse::<u64>().ok());
+        let modulus = modulus_arg(args.get(2..).unwrap_or(&[]));
+        let days = days.unwrap_or_else(|| {
+            eprintln!("usage: days <n> [--mod <m>]");
+            std::process::exit(1);
+        });
+        let total = fish
+            .advance(&population, days, modulus)
+            .map(|p| match modulus {
+                Some(m) => p.iter().fold(0, |sum, n| (sum + n) % m as u128),
+                None => total_pop(&p),
+            });
+        match (total, modulus) {
+            (Some(total), Some(m)) => println!("day {}: {} (mod {})", days, total, m),
+            (Some(total), None) => println!("day {}: {}", days, total),
+            (None, _) => {
+                eprintln!("day {}: too many fish for a u128; try --mod", days);
+                std::process::exit(1);
+            }
         }
     }
-    population.clear();
-    for (k, v) in next_map.iter() {
-        population.insert(*k, *v);
+
+    // `cargo run -- inverse <days> <total|c0,c1,...> [max fish] [limit]` lists
+    // starting populations that would give the observed total or per-timer
+    // counts on that day
+    if args.first().map(|s| s.as_str()) == Some("inverse") {
+        let days = args.get(1).and_then(|s| s.parse::<u64>().ok());
+        let observation = args.get(2).map(|s| inverse::Observation::parse(s));
+        let max_fish = args.get(3).map_or(Some(10), |s| s.parse::<u64>().ok());
+        let limit = args.get(4).map_or(Some(20), |s| s.parse::<usize>().ok());
+        let (days, observation, max_fish, limit) = match (days, observation, max_fish, limit) {
+            (Some(days), Some(Ok(observation)), Some(max_fish), Some(limit)) => {
+                (days, observation, max_fish, limit)
+            }
+            (_, Some(Err(error)), _, _) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+            _ => {
+                eprintln!("usage: inverse <days> <total|c0,c1,...> [max fish] [limit]");
+                std::process::exit(1);
+            }
+        };
+        let solutions =
+            inverse::solve(&fish, &observation, days, max_fish, limit).unwrap_or_else(|error| {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            });
+        let found = solutions.found.len();
+        match (found, solutions.complete) {
+            (0, _) => println!("no starting population of up to {} fish fits", max_fish),
+            (1, true) => println!("unique: 1 starting population of up to {} fish", max_fish),
+            (n, true) => println!(
+                "ambiguous: {} starting populations of up to {} fish",
+                n, max_fish
+            ),
+            (n, false) => println!(
+                "ambiguous: over {} starting populations of up to {} fish",
+                n, max_fish
+            ),
+        }
+        for counts in solutions.found {
+            let fish = counts.iter().sum::<u64>();
+            let counts = counts.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+            println!("  by timer: {} ({} fish)", counts.join(","), fish);
+        }
     }
+}
 
-    if spawn_count > 0 {
-        increment_count(&mut population, 8, spawn_count);
+// an optional `--mod <m>` at the start of `args`
+fn modulus_arg(args: &[String]) -> Option<u64> {
+    match args.first().map(|s| s.as_str()) {
+        Some("--mod") => match args.get(1).and_then(|s| s.parse::<u64>().ok()) {
+            Some(m) if m > 0 => Some(m),
+            _ => {
+                eprintln!("--mod needs a positive number");
+                std::process::exit(1);
+            }
+        },
+        _ => None,
     }
 }
 
-fn increment_count(map: &mut HashMap<usize, u64>, key: usize, value: u64) {
-    map.entry(key)
-        .and_modify(|count| *count += value)
-        .or_insert(value);
+fn parse(input: &str) -> Vec<usize> {
+    input
+        .split(",")
+        .map(|s| s.trim().parse::<usize>().unwrap())
+        .collect()
 }
 
-fn total_pop(map: &HashMap<usize, u64>) -> u64 {
-    map.values().sum::<u64>()
+fn total_pop(population: &Population) -> u128 {
+    population.iter().sum()
 }
@@ -0,0 +1,122 @@
+use crate::Population;
+
+// One day is a linear map on the population's state counts, so N days is the
+// Nth power of its matrix and takes O(log N) products by repeated squaring.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    n: usize,
+    cells: Vec<u128>,
+}
+
+impl Matrix {
+    pub fn zero(n: usize) -> Matrix {
+        Matrix {
+            n,
+            cells: vec![0; n * n],
+        }
+    }
+
+    fn identity(n: usize) -> Matrix {
+        let mut m = Matrix::zero(n);
+        for i in 0..n {
+            *m.at(i, i) = 1;
+        }
+        m
+    }
+
+    pub fn at(&mut self, row: usize, col: usize) -> &mut u128 {
+        &mut self.cells[row * self.n + col]
+    }
+
+    // None if a plain (modulus-free) product overflows u128
+    fn mul(&self, other: &Matrix, modulus: Option<u64>) -> Option<Matrix> {
+        let n = self.n;
+        let mut m = Matrix::zero(n);
+        for i in 0..n {
+            for k in 0..n {
+                let a = self.cells[i * n + k];
+                if a == 0 {
+                    continue;
+                }
+                for j in 0..n {
+                    m.cells[i * n + j] =
+                        add_mul(m.cells[i * n + j], a, other.cells[k * n + j], modulus)?;
+                }
+            }
+        }
+        Some(m)
+    }
+
+    pub fn pow(&self, mut n: u64, modulus: Option<u64>) -> Option<Matrix> {
+        let mut result = Matrix::identity(self.n);
+        let mut base = self.clone();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.mul(&base, modulus)?;
+            }
+            n >>= 1;
+            // the last square is never used, and might not fit
+            if n > 0 {
+                base = base.mul(&base, modulus)?;
+            }
+        }
+        Some(result)
+    }
+
+    pub fn apply(&self, population: &Population, modulus: Option<u64>) -> Option<Population> {
+        let mut next = vec![0; self.n];
+        for (i, cell) in next.iter_mut().enumerate() {
+            for (j, count) in population.iter().enumerate() {
+                *cell = add_mul(*cell, self.cells[i * self.n + j], *count, modulus)?;
+            }
+        }
+        Some(next)
+    }
+}
+
+// acc + a * b, exact or mod m; with m < 2^64 every reduced product fits
+pub fn add_mul(acc: u128, a: u128, b: u128, modulus: Option<u64>) -> Option<u128> {
+    match modulus {
+        None => acc.checked_add(a.checked_mul(b)?),
+        Some(m) => {
+            let m = m as u128;
+            Some((acc + (a % m) * (b % m) % m) % m)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lifecycle::Lifecycle;
+
+    #[test]
+    fn matches_day_by_day() {
+        let fish = Lifecycle::default();
+        let start = fish.population(&[3, 4, 3, 1, 2]).unwrap();
+        let mut population = start.clone();
+        for day in 1..=700 {
+            population = fish.step(&population, None).unwrap();
+            let jumped = fish.matrix().pow(day, None).unwrap().apply(&start, None);
+            assert_eq!(jumped, Some(population.clone()), "day {}", day);
+        }
+    }
+
+    #[test]
+    fn overflow_and_modular() {
+        let fish = Lifecycle::default();
+        let start = fish.population(&[3, 4, 3, 1, 2]).unwrap();
+        let total = |days, modulus| {
+            let population = fish.matrix().pow(days, modulus)?.apply(&start, modulus)?;
+            Some(population.iter().fold(0, |sum, n| match modulus {
+                Some(m) => (sum + n) % m as u128,
+                None => sum + n,
+            }))
+        };
+        assert_eq!(
+            total(256, Some(1_000_000_007)),
+            Some(26984457539 % 1_000_000_007)
+        );
+        assert_eq!(total(10_000, None), None);
+        assert!(total(1_000_000_000_000, Some(u64::MAX)).is_some());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::lifecycle::Lifecycle;
+use crate::Population;
+
+// One row per day from 0 to `days`: the count at each timer value, summed
+// over ages, then the total, all mod `modulus` if given. None if a plain
+// count overflows.
+pub fn time_series(
+    fish: &Lifecycle,
+    population: &Population,
+    days: u64,
+    modulus: Option<u64>,
+) -> Option<String> {
+    let reduce = |n: u128| match modulus {
+        Some(m) => n % m as u128,
+        None => n,
+    };
+    let mut header = vec!["day".to_string()];
+    header.extend((0..fish.timers()).map(|t| format!("timer_{}", t)));
+    header.push("total".to_string());
+    let mut lines = vec![header.join(",")];
+
+    let mut population = population.clone();
+    for day in 0..=days {
+        if day > 0 {
+            population = fish.step(&population, modulus)?;
+        }
+        let by_timer = fish
+            .by_timer(&population)
+            .into_iter()
+            .map(reduce)
+            .collect::<Vec<_>>();
+        let mut row = vec![day.to_string()];
+        row.extend(by_timer.iter().map(|n| n.to_string()));
+        let total = by_timer
+            .iter()
+            .try_fold(0u128, |total, n| total.checked_add(*n).map(reduce))?;
+        row.push(total.to_string());
+        lines.push(row.join(","));
+    }
+    Some(lines.join("\n"))
+}
+
+// The part of the day `days` population descended from each input fish,
+// itself included. Fish with the same starting timer have identical
+// lineages, so each distinct timer is simulated once.
+pub fn lineages(
+    fish: &Lifecycle,
+    timers: &[usize],
+    days: u64,
+    modulus: Option<u64>,
+) -> Option<Vec<u128>> {
+    let mut by_timer = vec![None; fish.timers()];
+    timers
+        .iter()
+        .map(|&timer| {
+            if by_timer[timer].is_none() {
+                let one = fish.population(&[timer]).ok()?;
+                let total = fish
+                    .advance(&one, days, modulus)?
+                    .iter()
+                    .fold(0u128, |sum, n| match modulus {
+                        Some(m) => (sum + n) % m as u128,
+                        None => sum + n,
+                    });
+                by_timer[timer] = Some(total);
+            }
+            by_timer[timer]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn series_and_lineages_add_up() {
+        let fish = Lifecycle::default();
+        let timers = [3, 4, 3, 1, 2];
+        let start = fish.population(&timers).unwrap();
+        let csv = time_series(&fish, &start, 18, None).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "day,timer_0,timer_1,timer_2,timer_3,timer_4,timer_5,timer_6,timer_7,timer_8,total"
+        );
+        assert_eq!(lines[1], "0,0,1,1,2,1,0,0,0,0,5");
+        assert_eq!(lines[3], "2,1,2,1,0,0,0,1,0,1,6");
+        assert!(lines[19].ends_with(",26"));
+
+        // past day 1000 the counts only fit mod something
+        assert_eq!(time_series(&fish, &start, 2000, None), None);
+        let csv = time_series(&fish, &start, 2000, Some(7)).unwrap();
+        let last = csv.lines().last().unwrap().split(',').collect::<Vec<_>>();
+        assert_eq!(last[0], "2000");
+        assert!(last[1..].iter().all(|n| n.parse::<u64>().unwrap() < 7));
+
+        let each = lineages(&fish, &timers, 80, None).unwrap();
+        assert_eq!(each[0], each[2]);
+        assert_eq!(each.iter().sum::<u128>(), 5934);
+    }
+}